@@ -0,0 +1,147 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::{SecondsFormat, Utc};
+use rocket::fairing::AdHoc;
+use rocket::http::Header;
+use rocket::serde::json::serde_json::json;
+use rocket::serde::Deserialize;
+use rocket::{Request, Response};
+use uuid::Uuid;
+
+use crate::extractors::ClientIp;
+use crate::ratelimit::{matches_prefix, rerouted};
+
+// One JSON line per request, configured under `[default.access_log]` in Rocket.toml.
+// Every response carries an `X-Request-Id`: the caller's (e.g. set by the proxy) when
+// it's a sane token, otherwise a fresh UUID. Query strings and bodies are never logged,
+// only their sizes; for `redact` prefixes the path is replaced by the route template.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    /// Append to this file instead of stdout.
+    pub file: Option<PathBuf>,
+    pub redact: Vec<String>,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        AccessLogConfig {
+            enabled: true,
+            file: None,
+            redact: ["/jwt-decode", "/hash", "/password"].map(String::from).to_vec(),
+        }
+    }
+}
+
+pub struct AccessLog {
+    redact: Vec<String>,
+    /// Stdout when unset.
+    file: Option<Mutex<File>>,
+}
+
+const REQUEST_ID: &str = "X-Request-Id";
+
+struct RequestStart {
+    id: String,
+    at: Instant,
+}
+
+impl AccessLog {
+    /// An unopenable log file aborts launch rather than dropping the log silently.
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Access log", |rocket| async {
+            let config = match rocket.figment().extract_inner::<AccessLogConfig>("access_log") {
+                Ok(config) => config,
+                Err(e) if e.missing() => AccessLogConfig::default(),
+                Err(e) => {
+                    error!("invalid [access_log] config: {}", e);
+                    return Err(rocket);
+                }
+            };
+            if !config.enabled {
+                return Ok(rocket);
+            }
+            let file = match &config.file {
+                None => None,
+                Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+                    Ok(file) => Some(Mutex::new(file)),
+                    Err(e) => {
+                        error!("cannot open access log {}: {}", path.display(), e);
+                        return Err(rocket);
+                    }
+                },
+            };
+            let log = AccessLog { redact: config.redact, file };
+            Ok(rocket
+                .manage(log)
+                .attach(AdHoc::on_request("Access log: request id", |req, _| {
+                    Box::pin(async move {
+                        req.local_cache(|| RequestStart { id: request_id(req), at: Instant::now() });
+                    })
+                }))
+                .attach(AdHoc::on_response("Access log: write", |req, res| {
+                    Box::pin(async move {
+                        let Some(log) = req.rocket().state::<AccessLog>() else { return };
+                        let start = req.local_cache(|| RequestStart { id: request_id(req), at: Instant::now() });
+                        res.set_header(Header::new(REQUEST_ID, start.id.clone()));
+                        let client_ip = req.guard::<ClientIp>().await.succeeded().map(|ClientIp(ip)| ip);
+                        log.write(req, res, start, client_ip);
+                    })
+                })))
+        })
+    }
+
+    fn write(&self, req: &Request<'_>, res: &Response<'_>, start: &RequestStart, client_ip: Option<String>) {
+        let (method, uri, route) = match rerouted(req) {
+            Some(original) => (original.method, &original.uri, original.route.clone()),
+            None => (req.method(), req.uri(), req.route().map(|r| r.uri.path().to_string())),
+        };
+        let raw_path = uri.path().as_str();
+        let path = if self.redact.iter().any(|prefix| matches_prefix(raw_path, prefix)) {
+            route.clone().unwrap_or_else(|| "<redacted>".to_string())
+        } else {
+            raw_path.to_string()
+        };
+        let body_bytes = req.headers().get_one("content-length").and_then(|v| v.parse::<u64>().ok());
+        let line = json!({
+            "ts": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "request_id": start.id,
+            "method": method.as_str(),
+            "route": route,
+            "path": path,
+            "status": res.status().code,
+            "latency_ms": start.at.elapsed().as_secs_f64() * 1000.0,
+            "client_ip": client_ip,
+            "query_bytes": uri.query().map_or(0, |q| q.as_str().len()),
+            "body_bytes": body_bytes.unwrap_or(0),
+            "response_bytes": res.body().preset_size(),
+        });
+        match &self.file {
+            // One write per line so concurrent requests never interleave within a line.
+            Some(file) => {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = file.write_all(format!("{}\n", line).as_bytes());
+            }
+            None => println!("{}", line),
+        }
+    }
+}
+
+/// The caller's `X-Request-Id` if it's a plausible id, so the proxy's ids carry through;
+/// anything long or odd (it ends up in our logs and headers) is replaced with a UUID.
+fn request_id(req: &Request<'_>) -> String {
+    req.headers()
+        .get_one(REQUEST_ID)
+        .map(str::trim)
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= 128
+                && id.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b))
+        })
+        .map_or_else(|| Uuid::new_v4().to_string(), str::to_string)
+}
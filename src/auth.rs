@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{NaiveDate, Utc};
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::Figment;
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::extractors::api_key;
+use crate::openapi;
+use crate::response::ApiError;
+
+// Optional API key authentication, configured under `[default.auth]` in Rocket.toml.
+// Keys come from `[[default.auth.keys]]` and/or a TOML `keys_file` with `[[keys]]`
+// tables; each has scopes (endpoint modules, i.e. OpenAPI tags, or "*") and an
+// optional daily quota. Only key hashes are kept in memory.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AuthConfig {
+    enabled: bool,
+    keys_file: Option<PathBuf>,
+    keys: Vec<KeyConfig>,
+}
+
+#[derive(Deserialize)]
+struct KeyConfig {
+    /// Label used for quotas and in error messages; never the key itself.
+    name: String,
+    /// The key in clear, or...
+    key: Option<String>,
+    /// ...its hex SHA-256, so config files needn't hold the secret.
+    key_sha256: Option<String>,
+    #[serde(default = "all_scopes")]
+    scopes: Vec<String>,
+    /// Requests per UTC day; unlimited when unset.
+    daily_quota: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct KeysFile {
+    keys: Vec<KeyConfig>,
+}
+
+fn all_scopes() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+/// Routes reachable without a key even when auth is enabled. They don't take the guard;
+/// this list only feeds the OpenAPI `security` requirements.
+pub const PUBLIC_ROUTES: &[&str] = &["index", "ping", "openapi_json", "docs"];
+
+struct KeyEntry {
+    name: String,
+    scopes: Vec<String>,
+    daily_quota: Option<u64>,
+}
+
+pub struct ApiKeys {
+    enabled: bool,
+    by_hash: HashMap<[u8; 32], KeyEntry>,
+    /// Per key name: the UTC day being counted and requests so far.
+    usage: Mutex<HashMap<String, (NaiveDate, u64)>>,
+}
+
+impl ApiKeys {
+    /// Loads keys at ignition. Malformed config, an unreadable keys file or a bad
+    /// `key_sha256` aborts launch: silently running without the intended keys is worse.
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("API keys", |rocket| async {
+            let config: AuthConfig = match rocket.figment().extract_inner("auth") {
+                Ok(config) => config,
+                Err(e) if e.missing() => AuthConfig::default(),
+                Err(e) => {
+                    error!("invalid [auth] config: {}", e);
+                    return Err(rocket);
+                }
+            };
+            match ApiKeys::load(config) {
+                Ok(keys) => Ok(rocket.manage(keys)),
+                Err(e) => {
+                    error!("failed to load API keys: {}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    fn load(config: AuthConfig) -> Result<ApiKeys, String> {
+        let mut keys = config.keys;
+        if let Some(path) = &config.keys_file {
+            let file: KeysFile = Figment::from(Toml::file(path))
+                .extract()
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            keys.extend(file.keys);
+        }
+
+        let mut by_hash = HashMap::new();
+        for key in keys {
+            let hash: [u8; 32] = match (&key.key, &key.key_sha256) {
+                (Some(k), None) => Sha256::digest(k.as_bytes()).into(),
+                (None, Some(h)) => hex::decode(h.trim())
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| format!("key '{}': key_sha256 must be 64 hex digits", key.name))?,
+                _ => return Err(format!("key '{}': set exactly one of key or key_sha256", key.name)),
+            };
+            let entry = KeyEntry { name: key.name, scopes: key.scopes, daily_quota: key.daily_quota };
+            by_hash.insert(hash, entry);
+        }
+        Ok(ApiKeys { enabled: config.enabled, by_hash, usage: Mutex::new(HashMap::new()) })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether `key` is one of the configured keys, whatever its scopes or quota.
+    pub fn knows(&self, key: &str) -> bool {
+        let hash: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+        self.by_hash.contains_key(&hash)
+    }
+
+    fn authorize(&self, key: Option<&str>, module: &str) -> Result<ApiKey, ApiError> {
+        let key = key.ok_or_else(|| {
+            ApiError::new(Status::Unauthorized, "unauthorized", "API key required (Authorization: Bearer or X-API-Key)")
+        })?;
+        let hash: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+        let entry = self
+            .by_hash
+            .get(&hash)
+            .ok_or_else(|| ApiError::new(Status::Unauthorized, "unauthorized", "unknown API key"))?;
+        if !entry.scopes.iter().any(|s| s == "*" || s == module) {
+            return Err(ApiError::new(
+                Status::Forbidden,
+                "forbidden",
+                format!("key '{}' is not allowed to use {} endpoints", entry.name, module),
+            ));
+        }
+
+        let today = Utc::now().date_naive();
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let (day, count) = usage.entry(entry.name.clone()).or_insert((today, 0));
+        if *day != today {
+            *day = today;
+            *count = 0;
+        }
+        if entry.daily_quota.is_some_and(|quota| *count >= quota) {
+            return Err(ApiError::new(
+                Status::TooManyRequests,
+                "quota_exceeded",
+                format!("daily quota of {} requests used up for key '{}'", entry.daily_quota.unwrap_or(0), entry.name),
+            ));
+        }
+        *count += 1;
+        Ok(ApiKey)
+    }
+}
+
+/// Request guard for every non-public endpoint. Always passes when auth is disabled;
+/// otherwise checks the key, the route's module scope and the daily quota.
+/// Failures are stashed for the catchers, which can't see guard errors themselves.
+#[derive(Debug)]
+pub struct ApiKey;
+
+pub struct AuthFailure(pub Option<ApiError>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiKey {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let keys = match request.rocket().state::<ApiKeys>() {
+            Some(keys) if keys.enabled => keys,
+            _ => return request::Outcome::Success(ApiKey),
+        };
+        let name = request.route().and_then(|r| r.name.as_deref()).unwrap_or_default();
+        let module = openapi::module_of(name).unwrap_or(name);
+        match keys.authorize(api_key(request), module) {
+            Ok(key) => request::Outcome::Success(key),
+            Err(e) => {
+                let status = e.status();
+                request.local_cache(|| AuthFailure(Some(e)));
+                request::Outcome::Error((status, ()))
+            }
+        }
+    }
+}
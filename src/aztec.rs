@@ -0,0 +1,531 @@
+// Aztec Code (ISO/IEC 24778) encoder. Bytes in, a square module grid out: the text is
+// packed into the five character modes and binary shift by a shortest-bit-length search,
+// stuffed into codewords, protected with Reed-Solomon check words and laid out in layers
+// around the bullseye.
+
+/// Share of the symbol given to check words, in percent; 23 is what the spec recommends.
+pub const DEFAULT_ECC_PERCENT: usize = 23;
+pub const MIN_ECC_PERCENT: usize = 5;
+pub const MAX_ECC_PERCENT: usize = 90;
+pub const MAX_LAYERS: usize = 32;
+pub const MAX_COMPACT_LAYERS: usize = 4;
+
+pub struct Aztec {
+    pub size: usize,
+    dark: Vec<bool>,
+}
+
+impl Aztec {
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.dark[y * self.size + x]
+    }
+}
+
+pub enum Error {
+    /// Doesn't fit in any symbol the options allow.
+    TooLarge,
+    /// Doesn't fit in the requested number of layers.
+    Layers(String),
+}
+
+/// `layers` forces a symbol size and `compact` the compact (1-4 layers) or full-range form;
+/// otherwise the smallest symbol that fits is used.
+pub fn encode(data: &[u8], ecc_percent: usize, layers: Option<usize>, compact: Option<bool>) -> Result<Aztec, Error> {
+    let bits = high_level(data);
+    let ecc_bits = bits.len() * ecc_percent / 100 + 11;
+    let candidates: Vec<(bool, usize)> = match layers {
+        Some(n) => vec![(compact.unwrap_or(false), n)],
+        // A full-range symbol is bigger than a compact one with the same layer count, so
+        // when either form will do, full-range only starts at 4 layers. Asked for
+        // full-range, the search starts at 1 layer.
+        None => {
+            let first_full = if compact == Some(false) { 1 } else { MAX_COMPACT_LAYERS };
+            (1..=MAX_COMPACT_LAYERS)
+                .map(|n| (true, n))
+                .chain((first_full..=MAX_LAYERS).map(|n| (false, n)))
+                .filter(|&(c, _)| compact.is_none_or(|want| want == c))
+                .collect()
+        }
+    };
+    for (compact, layers) in candidates {
+        let total_bits = total_bits_in_layers(layers, compact);
+        let word_size = word_size(layers);
+        let stuffed = stuff_bits(&bits, word_size);
+        // Compact mode messages count data words in six bits.
+        if stuffed.len() + ecc_bits > total_bits - total_bits % word_size || (compact && stuffed.len() > word_size * 64) {
+            continue;
+        }
+        let message = check_words(&stuffed, total_bits, word_size);
+        let mode = mode_message(compact, layers, stuffed.len() / word_size);
+        return Ok(draw(&message, &mode, compact, layers));
+    }
+    match layers {
+        Some(n) => Err(Error::Layers(format!(
+            "text doesn't fit in {} {} layer{} at this ecc level",
+            n,
+            if compact == Some(true) { "compact" } else { "full-range" },
+            if n == 1 { "" } else { "s" }
+        ))),
+        None => Err(Error::TooLarge),
+    }
+}
+
+fn total_bits_in_layers(layers: usize, compact: bool) -> usize {
+    (if compact { 88 } else { 112 } + 16 * layers) * layers
+}
+
+fn word_size(layers: usize) -> usize {
+    match layers {
+        0..=2 => 6,
+        3..=8 => 8,
+        9..=22 => 10,
+        _ => 12,
+    }
+}
+
+// ---- Character modes ----
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Upper,
+    Lower,
+    Mixed,
+    Punct,
+    Digit,
+}
+
+use Mode::*;
+
+const MODES: [Mode; 5] = [Upper, Lower, Mixed, Punct, Digit];
+
+impl Mode {
+    fn bits(self) -> usize {
+        if self == Digit {
+            4
+        } else {
+            5
+        }
+    }
+
+    /// The code for the character (or Punct's two-character pair) at the start of `s`, and
+    /// how many bytes it covers.
+    fn code(self, s: &[u8]) -> Option<(u16, usize)> {
+        let b = s[0];
+        let code = match (self, b) {
+            (Punct, _) => {
+                let pair = match s.get(..2) {
+                    Some(b"\r\n") => Some(2),
+                    Some(b". ") => Some(3),
+                    Some(b", ") => Some(4),
+                    Some(b": ") => Some(5),
+                    _ => None,
+                };
+                if let Some(code) = pair {
+                    return Some((code, 2));
+                }
+                if b == b'\r' {
+                    1
+                } else {
+                    6 + PUNCT.iter().position(|&p| p == b)? as u16
+                }
+            }
+            (Digit, b' ') => 1,
+            (Digit, b'0'..=b'9') => (b - b'0') as u16 + 2,
+            (Digit, b',') => 12,
+            (Digit, b'.') => 13,
+            (Digit, _) => return None,
+            (_, b' ') => 1,
+            (Upper, b'A'..=b'Z') => (b - b'A') as u16 + 2,
+            (Lower, b'a'..=b'z') => (b - b'a') as u16 + 2,
+            (Mixed, 1..=13) => b as u16 + 1,
+            (Mixed, 27..=31) => (b - 27) as u16 + 15,
+            (Mixed, _) => 20 + MIXED.iter().position(|&m| m == b)? as u16,
+            _ => return None,
+        };
+        Some((code, 1))
+    }
+}
+
+/// Punct codes 6-30.
+const PUNCT: &[u8] = b"!\"#$%&'()*+,-./:;<=>?[]{}";
+/// Mixed codes 20-27.
+const MIXED: &[u8] = b"@\\^_`|~\x7f";
+
+/// Codes that move from one mode to another for good; the shortest route, so going through
+/// Mixed to reach Punct is one entry.
+fn latch(from: Mode, to: Mode) -> &'static [(u16, usize)] {
+    match (from, to) {
+        (Upper, Lower) | (Mixed, Lower) => &[(28, 5)],
+        (Upper, Mixed) | (Lower, Mixed) => &[(29, 5)],
+        (Upper, Digit) | (Lower, Digit) => &[(30, 5)],
+        (Upper, Punct) | (Lower, Punct) => &[(29, 5), (30, 5)],
+        (Lower, Upper) => &[(30, 5), (14, 4)],
+        (Mixed, Upper) => &[(29, 5)],
+        (Mixed, Digit) => &[(29, 5), (30, 5)],
+        (Mixed, Punct) => &[(30, 5)],
+        (Punct, Upper) => &[(31, 5)],
+        (Punct, Lower) => &[(31, 5), (28, 5)],
+        (Punct, Mixed) => &[(31, 5), (29, 5)],
+        (Punct, Digit) => &[(31, 5), (30, 5)],
+        (Digit, Upper) => &[(14, 4)],
+        (Digit, Lower) => &[(14, 4), (28, 5)],
+        (Digit, Mixed) => &[(14, 4), (29, 5)],
+        (Digit, Punct) => &[(14, 4), (29, 5), (30, 5)],
+        _ => &[],
+    }
+}
+
+/// Longest binary shift: 31 plus an 11-bit extended count.
+const MAX_BINARY_RUN: usize = 31 + 2047;
+
+#[derive(Clone)]
+enum Step {
+    /// One character (or pair) in the current mode, optionally behind a one-character shift.
+    Char { shift: Option<(u16, usize)>, mode: Mode, code: u16 },
+    Binary(usize),
+}
+
+/// Finds the shortest bit string for `data`: a search over (bytes done, current mode), where
+/// each step emits one character, a shifted character or a binary-shift run, and modes can
+/// be latched between steps.
+fn high_level(data: &[u8]) -> Vec<bool> {
+    let n = data.len();
+    const NONE: usize = usize::MAX;
+    // `arrived`: cost after emitting into position i in mode m; `latched`: after latching.
+    let mut arrived = vec![[NONE; 5]; n + 1];
+    let mut latched = vec![[NONE; 5]; n + 1];
+    let mut arrived_from: Vec<[Option<(usize, Step)>; 5]> = vec![Default::default(); n + 1];
+    let mut latched_from = vec![[0usize; 5]; n + 1];
+    arrived[0][0] = 0;
+    for i in 0..=n {
+        for (to, &to_mode) in MODES.iter().enumerate() {
+            for (from, &from_mode) in MODES.iter().enumerate() {
+                if arrived[i][from] == NONE {
+                    continue;
+                }
+                let cost = arrived[i][from] + latch(from_mode, to_mode).iter().map(|&(_, b)| b).sum::<usize>();
+                if cost < latched[i][to] {
+                    latched[i][to] = cost;
+                    latched_from[i][to] = from;
+                }
+            }
+        }
+        if i == n {
+            break;
+        }
+        for (m, &mode) in MODES.iter().enumerate() {
+            let base = latched[i][m];
+            if base == NONE {
+                continue;
+            }
+            let mut relax = |j: usize, cost: usize, step: Step| {
+                if cost < arrived[j][m] {
+                    arrived[j][m] = cost;
+                    arrived_from[j][m] = Some((i, step));
+                }
+            };
+            if let Some((code, len)) = mode.code(&data[i..]) {
+                relax(i + len, base + mode.bits(), Step::Char { shift: None, mode, code });
+            }
+            // P/S from every mode but Punct; U/S from Lower and Digit.
+            if mode != Punct {
+                if let Some((code, len)) = Punct.code(&data[i..]) {
+                    relax(i + len, base + mode.bits() + 5, Step::Char { shift: Some((0, mode.bits())), mode: Punct, code });
+                }
+            }
+            if mode == Lower || mode == Digit {
+                if let Some((code, len)) = Upper.code(&data[i..]) {
+                    let us = if mode == Lower { 28 } else { 15 };
+                    relax(i + len, base + mode.bits() + 5, Step::Char { shift: Some((us, mode.bits())), mode: Upper, code });
+                }
+            }
+            if mode != Punct && mode != Digit {
+                for k in 1..=(n - i).min(MAX_BINARY_RUN) {
+                    let cost = base + 5 + if k <= 31 { 5 } else { 16 } + 8 * k;
+                    relax(i + k, cost, Step::Binary(k));
+                }
+            }
+        }
+    }
+
+    // Walk back from the cheapest final mode, collecting (latch, step) pairs.
+    let mut m = (0..5).min_by_key(|&m| arrived[n][m]).unwrap();
+    let mut steps = Vec::new();
+    let mut i = n;
+    while let Some((prev, step)) = arrived_from[i][m].clone() {
+        let from = latched_from[prev][m];
+        steps.push((from, m, prev, step));
+        i = prev;
+        m = from;
+    }
+    let mut bits = Vec::new();
+    for (from, to, at, step) in steps.into_iter().rev() {
+        for &(code, width) in latch(MODES[from], MODES[to]) {
+            push_bits(&mut bits, code as usize, width);
+        }
+        match step {
+            Step::Char { shift, mode, code } => {
+                if let Some((code, width)) = shift {
+                    push_bits(&mut bits, code as usize, width);
+                }
+                push_bits(&mut bits, code as usize, mode.bits());
+            }
+            Step::Binary(k) => {
+                push_bits(&mut bits, 31, 5);
+                if k <= 31 {
+                    push_bits(&mut bits, k, 5);
+                } else {
+                    push_bits(&mut bits, 0, 5);
+                    push_bits(&mut bits, k - 31, 11);
+                }
+                for &b in &data[at..at + k] {
+                    push_bits(&mut bits, b as usize, 8);
+                }
+            }
+        }
+    }
+    bits
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: usize, width: usize) {
+    bits.extend((0..width).rev().map(|i| value >> i & 1 == 1));
+}
+
+/// Splits the bit string into `word_size`-bit words, padding the last with ones. A word
+/// may not be all zeros or all ones, so a word whose first `word_size - 1` bits are all the
+/// same gets the opposite bit appended and the next word starts one bit earlier.
+fn stuff_bits(bits: &[bool], word_size: usize) -> Vec<bool> {
+    let mut out = Vec::new();
+    let mask = (1 << word_size) - 2;
+    let mut i = 0;
+    while i < bits.len() {
+        let mut word = 0;
+        for j in 0..word_size {
+            if bits.get(i + j).is_none_or(|&b| b) {
+                word |= 1 << (word_size - 1 - j);
+            }
+        }
+        let (word, taken) = if word & mask == mask {
+            (word & mask, word_size - 1)
+        } else if word & mask == 0 {
+            (word | 1, word_size - 1)
+        } else {
+            (word, word_size)
+        };
+        push_bits(&mut out, word, word_size);
+        i += taken;
+    }
+    out
+}
+
+// ---- Reed-Solomon ----
+
+/// GF(2^m) with the field polynomial Aztec uses for each word size.
+struct Field {
+    exp: Vec<usize>,
+    log: Vec<usize>,
+}
+
+impl Field {
+    fn new(word_size: usize) -> Field {
+        let poly = match word_size {
+            4 => 0x13,
+            6 => 0x43,
+            8 => 0x12d,
+            10 => 0x409,
+            _ => 0x1069,
+        };
+        let size = 1 << word_size;
+        let mut exp = vec![0; size];
+        let mut log = vec![0; size];
+        let mut x = 1;
+        for (i, e) in exp.iter_mut().enumerate() {
+            *e = x;
+            if i < size - 1 {
+                log[x] = i;
+            }
+            x <<= 1;
+            if x >= size {
+                x ^= poly;
+            }
+        }
+        Field { exp, log }
+    }
+
+    fn mul(&self, a: usize, b: usize) -> usize {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        let n = self.exp.len() - 1;
+        self.exp[(self.log[a] + self.log[b]) % n]
+    }
+
+    /// `count` check words for `data`, from the generator with roots α^1..α^count.
+    fn check_words(&self, data: &[usize], count: usize) -> Vec<usize> {
+        let mut generator = vec![1];
+        for i in 1..=count {
+            let root = self.exp[i % (self.exp.len() - 1)];
+            let mut next = vec![0; generator.len() + 1];
+            for (j, &g) in generator.iter().enumerate() {
+                next[j] ^= g;
+                next[j + 1] ^= self.mul(g, root);
+            }
+            generator = next;
+        }
+        let mut remainder = vec![0; count];
+        for &d in data {
+            let factor = d ^ remainder[0];
+            remainder.rotate_left(1);
+            remainder[count - 1] = 0;
+            for (r, &g) in remainder.iter_mut().zip(&generator[1..]) {
+                *r ^= self.mul(g, factor);
+            }
+        }
+        remainder
+    }
+}
+
+/// Data words plus check words filling `total_bits`, right-aligned (the spare bits lead).
+fn check_words(stuffed: &[bool], total_bits: usize, word_size: usize) -> Vec<bool> {
+    let words: Vec<usize> = stuffed
+        .chunks(word_size)
+        .map(|w| w.iter().fold(0, |acc, &b| acc << 1 | b as usize))
+        .collect();
+    let total_words = total_bits / word_size;
+    let check = Field::new(word_size).check_words(&words, total_words - words.len());
+    let mut bits = vec![false; total_bits % word_size];
+    for &w in words.iter().chain(&check) {
+        push_bits(&mut bits, w, word_size);
+    }
+    bits
+}
+
+/// Layer count and data word count, with GF(16) check words: 28 bits compact, 40 full.
+fn mode_message(compact: bool, layers: usize, data_words: usize) -> Vec<bool> {
+    let mut bits = Vec::new();
+    if compact {
+        push_bits(&mut bits, layers - 1, 2);
+        push_bits(&mut bits, data_words - 1, 6);
+        check_words(&bits, 28, 4)
+    } else {
+        push_bits(&mut bits, layers - 1, 5);
+        push_bits(&mut bits, data_words - 1, 11);
+        check_words(&bits, 40, 4)
+    }
+}
+
+// ---- Layout ----
+
+fn draw(message: &[bool], mode: &[bool], compact: bool, layers: usize) -> Aztec {
+    let base = if compact { 11 } else { 14 } + layers * 4;
+    // Full-range symbols get a reference-grid line every 16 modules out from the centre,
+    // so data coordinates skip over them.
+    let (size, map): (usize, Vec<usize>) = if compact {
+        (base, (0..base).collect())
+    } else {
+        let size = base + 1 + 2 * ((base / 2 - 1) / 15);
+        let (half, center) = (base / 2, size / 2);
+        let mut map = vec![0; base];
+        for i in 0..half {
+            let offset = i + i / 15;
+            map[half - i - 1] = center - offset - 1;
+            map[half + i] = center + offset + 1;
+        }
+        (size, map)
+    };
+    let mut dark = vec![false; size * size];
+    let mut set = |x: usize, y: usize| dark[y * size + x] = true;
+
+    // Each layer is two modules thick and is filled in four sides, clockwise from the top
+    // left, in pairs of bits across the layer.
+    let mut offset = 0;
+    for i in 0..layers {
+        let row = (layers - i) * 4 + if compact { 9 } else { 12 };
+        for j in 0..row {
+            for k in 0..2 {
+                let bit = |side: usize| message[offset + side * row * 2 + j * 2 + k];
+                if bit(0) {
+                    set(map[i * 2 + k], map[i * 2 + j]);
+                }
+                if bit(1) {
+                    set(map[i * 2 + j], map[base - 1 - i * 2 - k]);
+                }
+                if bit(2) {
+                    set(map[base - 1 - i * 2 - k], map[base - 1 - i * 2 - j]);
+                }
+                if bit(3) {
+                    set(map[base - 1 - i * 2 - j], map[i * 2 + k]);
+                }
+            }
+        }
+        offset += row * 8;
+    }
+
+    let center = size / 2;
+    if compact {
+        for i in 0..7 {
+            let at = center - 3 + i;
+            if mode[i] {
+                set(at, center - 5);
+            }
+            if mode[i + 7] {
+                set(center + 5, at);
+            }
+            if mode[20 - i] {
+                set(at, center + 5);
+            }
+            if mode[27 - i] {
+                set(center - 5, at);
+            }
+        }
+    } else {
+        for i in 0..10 {
+            let at = center - 5 + i + i / 5;
+            if mode[i] {
+                set(at, center - 7);
+            }
+            if mode[i + 10] {
+                set(center + 7, at);
+            }
+            if mode[29 - i] {
+                set(at, center + 7);
+            }
+            if mode[39 - i] {
+                set(center - 7, at);
+            }
+        }
+    }
+
+    // Bullseye rings, then the orientation marks at its corners.
+    let eye = if compact { 5 } else { 7 };
+    for ring in (0..eye).step_by(2) {
+        for j in center - ring..=center + ring {
+            set(j, center - ring);
+            set(j, center + ring);
+            set(center - ring, j);
+            set(center + ring, j);
+        }
+    }
+    for (x, y) in [
+        (center - eye, center - eye),
+        (center - eye + 1, center - eye),
+        (center - eye, center - eye + 1),
+        (center + eye, center - eye),
+        (center + eye, center - eye + 1),
+        (center + eye, center + eye - 1),
+    ] {
+        set(x, y);
+    }
+
+    if !compact {
+        for j in (0..base / 2 - 1).step_by(15).enumerate().map(|(n, _)| n * 16) {
+            for k in (center & 1..size).step_by(2) {
+                set(center - j, k);
+                set(center + j, k);
+                set(k, center - j);
+                set(k, center + j);
+            }
+        }
+    }
+    Aztec { size, dark }
+}
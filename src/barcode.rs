@@ -0,0 +1,237 @@
+// 1D symbologies: text in, bars (1 = dark module) and the human-readable line out. Bar
+// patterns come from barcoders; check digits, Code128 set selection and GS1 element
+// strings are worked out here first so callers get a specific error instead of a bare
+// "invalid data".
+
+use std::str::FromStr;
+
+use barcoders::sym::codabar::Codabar;
+use barcoders::sym::code128::Code128;
+use barcoders::sym::code39::Code39;
+use barcoders::sym::code93::Code93;
+use barcoders::sym::ean13::EAN13;
+use barcoders::sym::ean8::EAN8;
+use barcoders::sym::tf::TF;
+
+use crate::gs1;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Symbology {
+    Code128,
+    Gs1_128,
+    Code39,
+    Code93,
+    Codabar,
+    Ean13,
+    Ean8,
+    UpcA,
+    Itf14,
+    Itf,
+}
+
+pub const SYMBOLOGIES: &str = "code128, gs1-128, code39, code93, codabar, ean13, ean8, upca, itf14, itf";
+
+impl FromStr for Symbology {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Symbology, ()> {
+        Ok(match s.to_lowercase().replace('_', "-").as_str() {
+            "code128" => Symbology::Code128,
+            "gs1-128" | "gs1128" => Symbology::Gs1_128,
+            "code39" => Symbology::Code39,
+            "code93" => Symbology::Code93,
+            "codabar" => Symbology::Codabar,
+            "ean13" | "ean-13" => Symbology::Ean13,
+            "ean8" | "ean-8" => Symbology::Ean8,
+            "upca" | "upc-a" => Symbology::UpcA,
+            "itf14" | "itf-14" => Symbology::Itf14,
+            "itf" | "i2of5" => Symbology::Itf,
+            _ => return Err(()),
+        })
+    }
+}
+
+pub struct Bars {
+    pub bars: Vec<u8>,
+    /// What a person should read under the bars, check digits included.
+    pub text: String,
+}
+
+impl Symbology {
+    pub fn encode(self, data: &str) -> Result<Bars, String> {
+        let (bars, text) = match self {
+            Symbology::Code128 => {
+                let chars: Vec<char> = data.chars().collect();
+                (code128(&chars)?, data.to_string())
+            }
+            Symbology::Gs1_128 => {
+                let elements = gs1::parse(data)?;
+                let mut chars = vec![FNC1];
+                chars.extend(gs1::encode(&elements, FNC1).chars());
+                (code128(&chars)?, gs1::human_readable(&elements))
+            }
+            Symbology::Code39 => {
+                let bars = Code39::new(data).map_err(|_| "invalid data for Code39 (use A-Z, 0-9, space, -.$/+%)")?;
+                (bars.encode(), data.to_string())
+            }
+            Symbology::Code93 => (code93(data)?, data.to_string()),
+            Symbology::Codabar => (codabar(data)?, data.to_string()),
+            Symbology::Ean13 => {
+                let digits = with_check_digit(data, 13, "EAN-13")?;
+                (EAN13::new(&digits).map_err(|e| e.to_string())?.encode(), digits)
+            }
+            Symbology::Ean8 => {
+                let digits = with_check_digit(data, 8, "EAN-8")?;
+                (EAN8::new(&digits).map_err(|e| e.to_string())?.encode(), digits)
+            }
+            Symbology::UpcA => {
+                // UPC-A is EAN-13 with a leading zero.
+                let digits = with_check_digit(data, 12, "UPC-A")?;
+                (EAN13::new(format!("0{}", digits)).map_err(|e| e.to_string())?.encode(), digits)
+            }
+            Symbology::Itf14 => {
+                let digits = with_check_digit(data, 14, "ITF-14")?;
+                (TF::interleaved(&digits).map_err(|e| e.to_string())?.encode(), digits)
+            }
+            Symbology::Itf => {
+                if data.is_empty() || !data.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err("invalid data for ITF (use digits)".to_string());
+                }
+                // Pairs of digits are interleaved, so odd lengths get a check digit.
+                let mut digits = data.to_string();
+                if digits.len() % 2 == 1 {
+                    digits.push((b'0' + gs1::check_digit(&digits)) as char);
+                }
+                (TF::interleaved(&digits).map_err(|e| e.to_string())?.encode(), digits)
+            }
+        };
+        Ok(Bars { bars, text })
+    }
+}
+
+/// `len` digits, the last a GS1 check digit: appended when missing, verified when given.
+fn with_check_digit(data: &str, len: usize, name: &str) -> Result<String, String> {
+    if !data.bytes().all(|b| b.is_ascii_digit()) || !(len - 1..=len).contains(&data.len()) {
+        return Err(format!("invalid data for {} (use {}-{} digits)", name, len - 1, len));
+    }
+    let check = gs1::check_digit(&data[..len - 1]);
+    match data.as_bytes().get(len - 1) {
+        None => Ok(format!("{}{}", data, check)),
+        Some(&given) if given - b'0' == check => Ok(data.to_string()),
+        Some(&given) => Err(format!("{} check digit is {}, expected {}", name, given as char, check)),
+    }
+}
+
+/// Codabar needs start/stop characters (A-D); plain data is wrapped in A...A.
+fn codabar(data: &str) -> Result<Vec<u8>, String> {
+    let is_guard = |c: char| matches!(c, 'A'..='D');
+    let data = data.to_ascii_uppercase();
+    let full = match data.chars().filter(|&c| is_guard(c)).count() {
+        0 => format!("A{}A", data),
+        2 if data.len() > 2 && data.starts_with(is_guard) && data.ends_with(is_guard) => data,
+        _ => return Err("Codabar start/stop characters (A-D) go at both ends only".to_string()),
+    };
+    Codabar::new(&full)
+        .map(|b| b.encode())
+        .map_err(|_| "invalid data for Codabar (use 0-9, -$:/.+ between A-D start/stop characters)".to_string())
+}
+
+/// Code93 symbol values in order; the last four are the full-ASCII shift characters, which
+/// barcoders spells `(`, `)`, `[` and `[` again.
+const CODE93_VALUES: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%()[";
+/// The pattern for value 46, which barcoders can't look up by character.
+const CODE93_46: [u8; 9] = [1, 0, 0, 1, 1, 0, 0, 1, 0];
+
+/// barcoders looks the C and K check characters up by symbol, so a check value of 46 comes
+/// out as 45 (and K is then computed from the wrong C). Both are recomputed and written in.
+fn code93(data: &str) -> Result<Vec<u8>, String> {
+    let invalid = || "invalid data for Code93 (use A-Z, 0-9, space, -.$/+%)".to_string();
+    let mut bars = Code93::new(data).map_err(|_| invalid())?.encode();
+    let mut values: Vec<usize> = data.chars().map(|c| CODE93_VALUES.find(c)).collect::<Option<_>>().ok_or_else(invalid)?;
+    for cycle in [20, 15] {
+        let sum: usize = values.iter().rev().enumerate().map(|(i, &v)| v * (i % cycle + 1)).sum();
+        values.push(sum % 47);
+    }
+    // Start character, data, then C and K, nine modules each.
+    for (n, &value) in values.iter().enumerate().skip(data.chars().count()) {
+        let pattern = match CODE93_VALUES.chars().nth(value) {
+            Some(c) => Code93::new(c.to_string()).map_err(|_| invalid())?.encode()[9..18].to_vec(),
+            None => CODE93_46.to_vec(),
+        };
+        bars[9 * (n + 1)..9 * (n + 2)].copy_from_slice(&pattern);
+    }
+    Ok(bars)
+}
+
+// barcoders' Code128 control characters: start/switch to a set, and FNC1.
+const SET_A: char = 'À';
+const SET_B: char = 'Ɓ';
+const SET_C: char = 'Ć';
+const FNC1: char = '\u{0179}';
+
+/// Picks Code128 sets as it goes: C for runs of digits (two per symbol), A for control
+/// characters, B otherwise, following the switching rules of ISO/IEC 15417 annex E.
+fn code128(data: &[char]) -> Result<Vec<u8>, String> {
+    if data.iter().any(|&c| c != FNC1 && !c.is_ascii()) {
+        return Err("Code128 only encodes ASCII".to_string());
+    }
+    let digits = |i: usize| data[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let leading_fnc1 = data.iter().take_while(|&&c| c == FNC1).count();
+    let first_run = digits(leading_fnc1);
+    let start_c = first_run % 2 == 0 && (first_run >= 4 || (first_run == 2 && leading_fnc1 + 2 == data.len()));
+
+    let mut set = if start_c { SET_C } else { a_or_b(data) };
+    let mut out = String::from(set);
+    let mut i = 0;
+    while i < data.len() {
+        let c = data[i];
+        let run = digits(i);
+        if set == SET_C {
+            if c == FNC1 {
+                out.push(c);
+                i += 1;
+                continue;
+            }
+            if run >= 2 {
+                out.extend(&data[i..i + 2]);
+                i += 2;
+                continue;
+            }
+        } else if run >= 6 || (run >= 4 && (i == 0 || i + run == data.len())) {
+            // An odd run leaves its first digit in the current set.
+            if run % 2 == 1 {
+                out.push(c);
+                i += 1;
+            }
+            set = SET_C;
+            out.push(set);
+            continue;
+        }
+
+        let wanted = match c as u32 {
+            0..=31 => SET_A,
+            96..=127 => SET_B,
+            _ if c == FNC1 || set != SET_C => set,
+            _ => a_or_b(&data[i..]),
+        };
+        if wanted != set {
+            set = wanted;
+            out.push(set);
+        }
+        // DEL is spelled as ÷ in barcoders' set B table.
+        out.push(if c == '\x7f' { '\u{00f7}' } else { c });
+        i += 1;
+    }
+    Code128::new(&out).map(|b| b.encode()).map_err(|_| "invalid data for Code128".to_string())
+}
+
+/// Set A if a control character comes before any lowercase, else B.
+fn a_or_b(rest: &[char]) -> char {
+    rest.iter()
+        .find_map(|&c| match c as u32 {
+            0..=31 => Some(SET_A),
+            96..=127 => Some(SET_B),
+            _ => None,
+        })
+        .unwrap_or(SET_B)
+}
@@ -0,0 +1,58 @@
+use rocket::http::Status;
+use rocket::Request;
+
+use crate::auth::AuthFailure;
+use crate::response::ApiError;
+
+// Catchers for failures that never reach a handler, using the same JSON shape
+// as handler errors so clients only need one error parser.
+
+#[catch(400)]
+pub fn bad_request(req: &Request) -> ApiError {
+    ApiError::bad_request(format!("malformed request to {}", req.uri().path()))
+}
+
+/// 401/403/429 from the `ApiKey` guard, which leaves the specific error behind.
+fn auth_failure(req: &Request, fallback: ApiError) -> ApiError {
+    match req.local_cache(|| AuthFailure(None)) {
+        AuthFailure(Some(e)) => e.clone(),
+        AuthFailure(None) => fallback,
+    }
+}
+
+#[catch(401)]
+pub fn unauthorized(req: &Request) -> ApiError {
+    auth_failure(req, ApiError::new(Status::Unauthorized, "unauthorized", "API key required"))
+}
+
+#[catch(403)]
+pub fn forbidden(req: &Request) -> ApiError {
+    auth_failure(req, ApiError::new(Status::Forbidden, "forbidden", "not allowed"))
+}
+
+#[catch(404)]
+pub fn not_found(req: &Request) -> ApiError {
+    ApiError::new(Status::NotFound, "not_found", format!("no route for {} {}", req.method(), req.uri().path()))
+}
+
+#[catch(413)]
+pub fn payload_too_large(_req: &Request) -> ApiError {
+    ApiError::too_large("request body too large")
+}
+
+/// Rocket forwards with 422 when a path or query parameter is missing or doesn't
+/// parse, e.g. `/text/reverse` without `?text=` or `/lorem/abc`.
+#[catch(422)]
+pub fn unprocessable(req: &Request) -> ApiError {
+    ApiError::invalid(format!("missing or invalid parameters for {}", req.uri().path()))
+}
+
+#[catch(429)]
+pub fn too_many_requests(req: &Request) -> ApiError {
+    auth_failure(req, ApiError::new(Status::TooManyRequests, "quota_exceeded", "quota exceeded"))
+}
+
+#[catch(500)]
+pub fn internal_error(_req: &Request) -> ApiError {
+    ApiError::internal("internal server error")
+}
@@ -0,0 +1,282 @@
+// Free-form dates for the /time endpoints: RFC 3339, RFC 2822, ISO 8601 week dates, plain
+// `YYYY-MM-DD HH:MM` forms, unix epochs in a stated unit, and a small English grammar of
+// relative expressions ("next friday 9am", "3 days ago", "in 2 hours", "tomorrow noon").
+// Wall-clock results come back unplaced so the caller decides which zone they belong to
+// and what to do when DST repeats or skips them.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Days, Duration, FixedOffset, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+
+pub enum When {
+    /// An exact instant: the input carried an offset, was an epoch, or was a fixed number of
+    /// hours, minutes or seconds from now.
+    Instant(DateTime<Utc>),
+    /// A wall-clock time, still to be placed in a zone.
+    Local(NaiveDateTime),
+}
+
+pub struct Parsed {
+    /// rfc3339, rfc2822, iso_week, datetime, epoch or relative.
+    pub format: &'static str,
+    pub when: When,
+}
+
+/// Unit for bare integer inputs.
+#[derive(Clone, Copy)]
+pub enum EpochUnit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl FromStr for EpochUnit {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_lowercase().as_str() {
+            "s" | "seconds" => Ok(EpochUnit::Seconds),
+            "ms" | "milliseconds" => Ok(EpochUnit::Milliseconds),
+            "us" | "µs" | "microseconds" => Ok(EpochUnit::Microseconds),
+            "ns" | "nanoseconds" => Ok(EpochUnit::Nanoseconds),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Reads `input`; relative expressions count from `now`, in `now`'s zone.
+pub fn parse(input: &str, now: DateTime<Tz>, unit: EpochUnit) -> Result<Parsed, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty date".to_string());
+    }
+    let parsed = |format, when| Ok(Parsed { format, when });
+    if let Ok(n) = input.strip_prefix('@').unwrap_or(input).parse::<i64>() {
+        return match epoch(n, unit) {
+            Some(instant) => parsed("epoch", When::Instant(instant)),
+            None => Err(format!("epoch {} is out of range", n)),
+        };
+    }
+    if let Some(dt) = with_offset(input) {
+        return parsed("rfc3339", When::Instant(dt.with_timezone(&Utc)));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(input) {
+        return parsed("rfc2822", When::Instant(dt.with_timezone(&Utc)));
+    }
+    if let Some(local) = iso_week(input) {
+        return parsed("iso_week", When::Local(local));
+    }
+    if let Some(local) = naive(input) {
+        return parsed("datetime", When::Local(local));
+    }
+    match relative(input, now) {
+        Some(when) => parsed("relative", when),
+        None => Err(format!(
+            "can't read '{}' (try 2026-10-17T09:00, RFC 3339 or 2822, 2026-W42-5, \"next friday 9am\" or \"3 days ago\")",
+            input
+        )),
+    }
+}
+
+fn epoch(n: i64, unit: EpochUnit) -> Option<DateTime<Utc>> {
+    match unit {
+        EpochUnit::Seconds => DateTime::from_timestamp(n, 0),
+        EpochUnit::Milliseconds => DateTime::from_timestamp_millis(n),
+        EpochUnit::Microseconds => DateTime::from_timestamp_micros(n),
+        EpochUnit::Nanoseconds => Some(DateTime::from_timestamp_nanos(n)),
+    }
+}
+
+/// RFC 3339, also without seconds (`2026-10-17T09:00+04:00`, `2026-10-17 05:00Z`).
+fn with_offset(input: &str) -> Option<DateTime<FixedOffset>> {
+    const FORMATS: [&str; 4] = ["%Y-%m-%dT%H:%M:%S%.f%:z", "%Y-%m-%dT%H:%M%:z", "%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%d %H:%M%:z"];
+    let input = match input.strip_suffix(['Z', 'z']) {
+        Some(utc) => format!("{}+00:00", utc),
+        None => input.to_string(),
+    };
+    FORMATS.iter().find_map(|f| DateTime::parse_from_str(&input, f).ok())
+}
+
+/// `2026-10-17T09:00` or `2026/10/17 09:00`, with optional seconds and fraction; a bare date
+/// is midnight.
+fn naive(input: &str) -> Option<NaiveDateTime> {
+    let input = input.replace('/', "-");
+    let (date, time) = match input.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (input.as_str(), None),
+    };
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.and_time(match time {
+        Some(time) => clock(time)?,
+        None => NaiveTime::MIN,
+    }))
+}
+
+fn clock(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M:%S%.f").or_else(|_| NaiveTime::parse_from_str(time, "%H:%M")).ok()
+}
+
+/// `2026-W42`, `2026-W42-5` or `2026W425`, optionally followed by `T09:00`; the week's
+/// Monday when the day is left off.
+fn iso_week(input: &str) -> Option<NaiveDateTime> {
+    let (date, time) = match input.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (input, None),
+    };
+    let (year, rest) = date.split_once(['W', 'w'])?;
+    let year = year.strip_suffix('-').unwrap_or(year);
+    if year.len() != 4 || !rest.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+        return None;
+    }
+    let rest = rest.replace('-', "");
+    let week: u32 = rest.get(..2)?.parse().ok()?;
+    let day = match &rest[2..] {
+        "" => Weekday::Mon,
+        day => Weekday::try_from(day.parse::<u8>().ok()?.checked_sub(1)?).ok()?,
+    };
+    let date = NaiveDate::from_isoywd_opt(year.parse().ok()?, week, day)?;
+    Some(date.and_time(match time {
+        Some(time) => clock(time)?,
+        None => NaiveTime::MIN,
+    }))
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+fn unit(word: &str) -> Option<Unit> {
+    Some(match word {
+        "s" | "sec" | "secs" | "second" | "seconds" => Unit::Second,
+        "m" | "min" | "mins" | "minute" | "minutes" => Unit::Minute,
+        "h" | "hr" | "hrs" | "hour" | "hours" => Unit::Hour,
+        "d" | "day" | "days" => Unit::Day,
+        "w" | "wk" | "wks" | "week" | "weeks" => Unit::Week,
+        "mo" | "month" | "months" => Unit::Month,
+        "y" | "yr" | "yrs" | "year" | "years" => Unit::Year,
+        _ => return None,
+    })
+}
+
+fn count(word: &str) -> Option<i64> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        n if n.bytes().all(|b| b.is_ascii_digit()) => n.parse().ok().filter(|&n| n <= 1_000_000),
+        _ => None,
+    }
+}
+
+/// `now` moved by `n` units: hours and smaller are exact durations, days and larger keep the
+/// wall-clock time (months clamp to the end of shorter months).
+fn shift(now: DateTime<Tz>, n: i64, unit: Unit) -> Option<When> {
+    let local = now.naive_local();
+    let months = |m: i64| {
+        let months = Months::new(u32::try_from(m.unsigned_abs()).ok()?);
+        if m < 0 {
+            local.checked_sub_months(months)
+        } else {
+            local.checked_add_months(months)
+        }
+    };
+    Some(match unit {
+        Unit::Second => When::Instant(now.with_timezone(&Utc).checked_add_signed(Duration::try_seconds(n)?)?),
+        Unit::Minute => When::Instant(now.with_timezone(&Utc).checked_add_signed(Duration::try_minutes(n)?)?),
+        Unit::Hour => When::Instant(now.with_timezone(&Utc).checked_add_signed(Duration::try_hours(n)?)?),
+        Unit::Day => When::Local(local.checked_add_signed(Duration::try_days(n)?)?),
+        Unit::Week => When::Local(local.checked_add_signed(Duration::try_weeks(n)?)?),
+        Unit::Month => When::Local(months(n)?),
+        Unit::Year => When::Local(months(n.checked_mul(12)?)?),
+    })
+}
+
+/// "now", "3 days ago", "in 2 hours", "next week", or a day ("today", "tomorrow",
+/// "yesterday", "friday", "this/next/last friday") and/or a time of day ("9am", "9:30 pm",
+/// "17:45", "noon", "midnight"). A bare weekday or "this friday" may be today, "next friday"
+/// is the first Friday after today and "last friday" the last one before it.
+fn relative(input: &str, now: DateTime<Tz>) -> Option<When> {
+    let lower = input.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty() && *w != "at")
+        .collect();
+    match words.as_slice() {
+        ["now"] => return Some(When::Instant(now.with_timezone(&Utc))),
+        [n, u, "ago"] => return shift(now, -count(n)?, unit(u)?),
+        ["in", n, u] => return shift(now, count(n)?, unit(u)?),
+        ["next", u] if unit(u).is_some() => return shift(now, 1, unit(u)?),
+        ["last", u] if unit(u).is_some() => return shift(now, -1, unit(u)?),
+        _ => {}
+    }
+
+    let today = now.date_naive();
+    let days_to = |day: Weekday| (day.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let (date, rest) = match words.as_slice() {
+        ["today", rest @ ..] => (today, rest),
+        ["tomorrow", rest @ ..] => (today.succ_opt()?, rest),
+        ["yesterday", rest @ ..] => (today.pred_opt()?, rest),
+        ["next", day, rest @ ..] => {
+            let ahead = match days_to(day.parse().ok()?) {
+                0 => 7,
+                n => n,
+            };
+            (today.checked_add_days(Days::new(ahead.into()))?, rest)
+        }
+        ["last", day, rest @ ..] => {
+            let back = match (7 - days_to(day.parse().ok()?)) % 7 {
+                0 => 7,
+                n => n,
+            };
+            (today.checked_sub_days(Days::new(back.into()))?, rest)
+        }
+        ["this", day, rest @ ..] => (today.checked_add_days(Days::new(days_to(day.parse().ok()?).into()))?, rest),
+        [day, rest @ ..] if day.parse::<Weekday>().is_ok() => {
+            (today.checked_add_days(Days::new(days_to(day.parse().ok()?).into()))?, rest)
+        }
+        // Just a time of day: today at that time.
+        rest => (today, rest),
+    };
+    let time = match rest {
+        [] => NaiveTime::MIN,
+        words => time_of_day(&words.concat())?,
+    };
+    Some(When::Local(date.and_time(time)))
+}
+
+/// "9am", "9:30pm", "17:45", "17:45:30", "noon" or "midnight"; a bare hour needs am/pm.
+fn time_of_day(s: &str) -> Option<NaiveTime> {
+    match s {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, pm) = match (s.strip_suffix("am"), s.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (s, None),
+    };
+    if clock.is_empty() || !clock.bytes().all(|b| b.is_ascii_digit() || b == b':') || (pm.is_none() && !clock.contains(':')) {
+        return None;
+    }
+    let mut parts = clock.split(':').map(str::parse::<u32>);
+    let hour = parts.next()?.ok()?;
+    let minute = parts.next().unwrap_or(Ok(0)).ok()?;
+    let second = parts.next().unwrap_or(Ok(0)).ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let hour = match pm {
+        None => hour,
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
@@ -1,10 +1,13 @@
-use std::collections::HashMap;
-use rocket::figment::value::Map;
-use rocket::serde::json::Json;
+use base64::{Engine as _, engine::general_purpose};
+use std::net::IpAddr;
+use rocket::State;
 use rocket_dyn_templates::{context, Template};
 
+use crate::auth::ApiKey;
 use crate::extractors::{AllHeaders, ClientIp};
-use crate::types::{EchoResponse, IpInfoResponse, WhoamiResponse};
+use crate::geoip::GeoIp;
+use crate::response::{ApiError, Reply};
+use crate::types::{EchoResponse, WhoamiResponse};
 
 // Root endpoint
 #[get("/")]
@@ -14,54 +17,109 @@ pub fn index() -> Template {
 
 // Basic endpoints
 #[get("/whoami")]
-pub fn whoami(whoami: WhoamiResponse) -> Json<WhoamiResponse> {
-    Json(whoami)
+pub fn whoami(_key: ApiKey, whoami: WhoamiResponse) -> Reply {
+    Reply::json(whoami)
 }
 
 #[get("/ip")]
-pub fn ip(client_ip: ClientIp) -> String {
-    client_ip.0
+pub fn ip(_key: ApiKey, client_ip: ClientIp) -> Reply {
+    Reply::text(client_ip.0)
 }
 
+// Geolocation comes from local MMDB files (see geoip.rs) - no external API calls
 #[get("/ip-info")]
-pub fn ip_info(client_ip: ClientIp) -> Json<IpInfoResponse> {
-    // Simplified IP info without external API call
-    Json(IpInfoResponse {
-        ip: client_ip.0,
-        city: Some("Unknown".to_string()),
-        region: Some("Unknown".to_string()),
-        country: Some("Unknown".to_string()),
-        asn: Some("Unknown".to_string()),
-        org: Some("Unknown".to_string()),
-    })
+pub fn ip_info(_key: ApiKey, client_ip: ClientIp, geoip: &State<GeoIp>) -> Reply {
+    lookup_ip(&client_ip.0, geoip)
+}
+
+/// `/ip-info/8.8.8.8` -> geolocation for an arbitrary address
+#[get("/ip-info/<ip>")]
+pub fn ip_info_for(_key: ApiKey, ip: String, geoip: &State<GeoIp>) -> Reply {
+    lookup_ip(&ip, geoip)
+}
+
+fn lookup_ip(ip: &str, geoip: &GeoIp) -> Reply {
+    let ip: IpAddr = match ip.trim().parse() {
+        Ok(ip) => ip,
+        Err(_) => return ApiError::invalid(format!("'{}' is not a valid IP address", ip)).into(),
+    };
+    if !geoip.is_configured() {
+        return ApiError::not_configured(
+            "no geolocation database configured (set geoip.city_db / geoip.asn_db in Rocket.toml)",
+        )
+        .with("ip", ip.to_string())
+        .into();
+    }
+    match geoip.lookup(ip) {
+        Ok(info) => Reply::json(info),
+        Err(e) => ApiError::internal(format!("geolocation lookup failed: {}", e)).into(),
+    }
 }
 
 #[get("/headers")]
-pub fn headers(all_headers: AllHeaders) -> Json<Map<String, String>> {
-    Json(all_headers.0)
+pub fn headers(_key: ApiKey, all_headers: AllHeaders) -> Reply {
+    Reply::json(all_headers.0)
 }
 
 #[get("/user-agent")]
-pub fn user_agent(all_headers: AllHeaders) -> String {
-    all_headers.0
+pub fn user_agent(_key: ApiKey, all_headers: AllHeaders) -> Reply {
+    Reply::text(all_headers.0
         .get("user-agent")
         .cloned()
-        .unwrap_or_else(|| "Unknown".to_string())
+        .unwrap_or_else(|| "Unknown".to_string()))
 }
 
+// Echo endpoints - one route per method so /echo works as an httpbin-style mirror.
+// GET/HEAD/OPTIONS don't carry a payload in Rocket, so only the others read the body.
 #[get("/echo")]
-pub fn echo(all_headers: AllHeaders) -> Json<EchoResponse> {
-    let query_map: HashMap<String, String> = HashMap::new();
-    
-    Json(EchoResponse {
-        method: "GET".to_string(),
-        query: query_map,
-        headers: all_headers.0,
-        body: "".to_string(), // GET requests don't have body
-    })
+pub fn echo(_key: ApiKey, echo: EchoResponse) -> Reply {
+    Reply::json(echo)
+}
+
+#[head("/echo")]
+pub fn echo_head(_key: ApiKey, echo: EchoResponse) -> Reply {
+    Reply::json(echo)
+}
+
+#[options("/echo")]
+pub fn echo_options(_key: ApiKey, echo: EchoResponse) -> Reply {
+    Reply::json(echo)
+}
+
+#[post("/echo", data = "<body>")]
+pub fn echo_post(_key: ApiKey, echo: EchoResponse, body: Vec<u8>) -> Reply {
+    Reply::json(with_body(echo, body))
+}
+
+#[put("/echo", data = "<body>")]
+pub fn echo_put(_key: ApiKey, echo: EchoResponse, body: Vec<u8>) -> Reply {
+    Reply::json(with_body(echo, body))
+}
+
+#[patch("/echo", data = "<body>")]
+pub fn echo_patch(_key: ApiKey, echo: EchoResponse, body: Vec<u8>) -> Reply {
+    Reply::json(with_body(echo, body))
+}
+
+#[delete("/echo", data = "<body>")]
+pub fn echo_delete(_key: ApiKey, echo: EchoResponse, body: Vec<u8>) -> Reply {
+    Reply::json(with_body(echo, body))
+}
+
+// Body is capped by the `bytes` limit in Rocket.toml; non-UTF-8 bodies come back as base64.
+fn with_body(mut echo: EchoResponse, body: Vec<u8>) -> EchoResponse {
+    echo.content_length = Some(body.len());
+    match String::from_utf8(body) {
+        Ok(text) => echo.body = text,
+        Err(e) => {
+            echo.body = general_purpose::STANDARD.encode(e.as_bytes());
+            echo.body_encoding = "base64".to_string();
+        }
+    }
+    echo
 }
 
 #[get("/ping")]
-pub fn ping() -> &'static str {
-    "pong"
+pub fn ping() -> Reply {
+    Reply::text("pong")
 }
@@ -1,11 +1,14 @@
-use rocket::serde::json::{serde_json, Json, Value};
+use rocket::serde::json::serde_json;
+
+use crate::auth::ApiKey;
+use crate::response::{ApiError, Reply};
 
 /// Convert an integer between bases 2..=36.
 /// `/base/convert?value=255&from=10&to=16` -> "ff"
 #[get("/base/convert?<value>&<from>&<to>")]
-pub fn base_convert(value: String, from: Option<String>, to: Option<String>) -> Json<Value> {
+pub fn base_convert(_key: ApiKey, value: String, from: Option<String>, to: Option<String>) -> Reply {
     if value.len() > 256 {
-        return Json(serde_json::json!({"error": "input too large"}));
+        return ApiError::too_large("input too large").into();
     }
     let (from, to) = match (
         from.as_deref().map(str::trim).map(str::parse::<u32>),
@@ -13,25 +16,22 @@ pub fn base_convert(value: String, from: Option<String>, to: Option<String>) ->
     ) {
         (Some(Ok(f)), Some(Ok(t))) => (f, t),
         _ => {
-            return Json(serde_json::json!({
-                "error": "provide from and to as integer bases, e.g. ?value=255&from=10&to=16"
-            }))
+            return ApiError::bad_request("provide from and to as integer bases, e.g. ?value=255&from=10&to=16")
+                .into()
         }
     };
     if !(2..=36).contains(&from) || !(2..=36).contains(&to) {
-        return Json(serde_json::json!({"error": "base must be between 2 and 36"}));
+        return ApiError::invalid("base must be between 2 and 36").into();
     }
     match i128::from_str_radix(value.trim(), from) {
-        Ok(n) => Json(serde_json::json!({
+        Ok(n) => Reply::json(serde_json::json!({
             "input": value,
             "from_base": from,
             "to_base": to,
             "decimal": n.to_string(),
             "result": to_radix(n, to),
         })),
-        Err(_) => Json(serde_json::json!({
-            "error": format!("'{}' is not a valid base-{} number", value, from)
-        })),
+        Err(_) => ApiError::invalid(format!("'{}' is not a valid base-{} number", value, from)).into(),
     }
 }
 
@@ -57,40 +57,44 @@ fn to_radix(n: i128, radix: u32) -> String {
 
 /// `/color/hex-to-rgb?hex=ff8800`
 #[get("/color/hex-to-rgb?<hex>")]
-pub fn hex_to_rgb(hex: String) -> Json<Value> {
-    let h = hex.trim().trim_start_matches('#');
-    if h.len() != 6 || !h.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Json(serde_json::json!({"error": "expected a 6-digit hex color like ff8800"}));
-    }
-    let r = u8::from_str_radix(&h[0..2], 16).unwrap();
-    let g = u8::from_str_radix(&h[2..4], 16).unwrap();
-    let b = u8::from_str_radix(&h[4..6], 16).unwrap();
-    Json(serde_json::json!({
-        "hex": format!("#{}", h.to_lowercase()),
+pub fn hex_to_rgb(_key: ApiKey, hex: String) -> Reply {
+    let Some([r, g, b]) = parse_hex_color(&hex) else {
+        return ApiError::invalid("expected a 6-digit hex color like ff8800").into();
+    };
+    Reply::json(serde_json::json!({
+        "hex": format!("#{:02x}{:02x}{:02x}", r, g, b),
         "r": r, "g": g, "b": b,
         "rgb": format!("rgb({}, {}, {})", r, g, b),
     }))
 }
 
+/// `ff8800` or `#ff8800` -> [r, g, b]. Shared with the code generators' color options.
+pub fn parse_hex_color(hex: &str) -> Option<[u8; 3]> {
+    let h = hex.trim().trim_start_matches('#');
+    if h.len() != 6 || !h.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&h[i..i + 2], 16).unwrap();
+    Some([channel(0), channel(2), channel(4)])
+}
+
 /// `/color/rgb-to-hex?r=255&g=136&b=0`
 #[get("/color/rgb-to-hex?<r>&<g>&<b>")]
-pub fn rgb_to_hex(r: Option<String>, g: Option<String>, b: Option<String>) -> Json<Value> {
+pub fn rgb_to_hex(_key: ApiKey, r: Option<String>, g: Option<String>, b: Option<String>) -> Reply {
     let parse = |o: Option<String>| -> Option<i64> {
         o.as_deref().and_then(|s| s.trim().parse::<i64>().ok())
     };
     let (r, g, b) = match (parse(r), parse(g), parse(b)) {
         (Some(r), Some(g), Some(b)) => (r, g, b),
         _ => {
-            return Json(serde_json::json!({
-                "error": "provide r, g, b as integers, e.g. ?r=255&g=136&b=0"
-            }))
+            return ApiError::bad_request("provide r, g, b as integers, e.g. ?r=255&g=136&b=0").into()
         }
     };
     // Range-check explicitly so the message always fires (not swallowed by integer parsing).
     if !(0..=255).contains(&r) || !(0..=255).contains(&g) || !(0..=255).contains(&b) {
-        return Json(serde_json::json!({"error": "each channel must be 0-255"}));
+        return ApiError::invalid("each channel must be 0-255").into();
     }
-    Json(serde_json::json!({
+    Reply::json(serde_json::json!({
         "hex": format!("#{:02x}{:02x}{:02x}", r as u8, g as u8, b as u8),
         "r": r, "g": g, "b": b,
     }))
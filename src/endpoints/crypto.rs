@@ -1,72 +1,426 @@
 use base64::{Engine as _, engine::general_purpose};
-use md5;
-use sha1;
-use sha2::{Digest, Sha256};
-use rocket::serde::json::{Json, Value, serde_json};
+use chrono::{TimeZone, Utc};
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512, Sha512_256};
+use rocket::serde::json::{self, Json, Value, serde_json};
+use rocket::serde::Deserialize;
+
+use crate::auth::ApiKey;
+use crate::endpoints::time::humanize;
+use crate::extractors::BodyInput;
+use crate::response::{ApiError, Reply};
+
+// SECURITY FIX: Limit input size to prevent DoS attacks
+pub const MAX_HASH_INPUT: usize = 1_000_000;
+/// Cap on POSTed hash and hmac input: the `bytes`/`json` limits in Rocket.toml, which cut
+/// bodies off first.
+pub const MAX_HASH_BODY: usize = 256_000;
+
+const HASH_ALGOS: &str = "md5, sha1, sha224, sha256, sha384, sha512, sha512-256, \
+    sha3-224, sha3-256, sha3-384, sha3-512, blake2b, blake2s, blake3, crc32, xxh32, xxh64, xxh3";
 
 // Crypto endpoints
-#[get("/hash/<algo>/<text>")]
-pub fn hash(algo: String, text: String) -> String {
-    // SECURITY FIX: Limit input size to prevent DoS attacks
-    if text.len() > 1_000_000 {
-        return "Error: Input too large (max 1MB)".to_string();
-    }
-    match algo.to_lowercase().as_str() {
-        "md5" => {
-            format!("{:x}", md5::compute(text.as_bytes()))
+/// `/hash/sha256/hello?output=base64` - output: hex (default), HEX, base64, base64url
+#[get("/hash/<algo>/<text>?<output>")]
+pub fn hash(_key: ApiKey, algo: String, text: String, output: Option<String>) -> Reply {
+    if text.len() > MAX_HASH_INPUT {
+        return ApiError::too_large("Input too large (max 1MB)").into();
+    }
+    hash_output(&algo, text.as_bytes(), output.as_deref())
+}
+
+/// `POST /hash/sha256` hashes the raw request body (or `{"input": ...}` JSON), e.g. a file.
+#[post("/hash/<algo>?<output>", data = "<body>")]
+pub fn hash_body(_key: ApiKey, algo: String, output: Option<String>, body: Result<BodyInput, ApiError>) -> Reply {
+    match body {
+        Ok(b) if b.0.len() > MAX_HASH_BODY => ApiError::too_large("Input too large (max 256 kB)").into(),
+        Ok(b) => hash_output(&algo, &b.0, output.as_deref()),
+        Err(e) => e.into(),
+    }
+}
+
+fn hash_output(algo: &str, bytes: &[u8], output: Option<&str>) -> Reply {
+    let digest = match digest_bytes(&algo.to_lowercase(), bytes) {
+        Some(d) => d,
+        None => {
+            return ApiError::unsupported(format!("Unsupported hash algorithm (supported: {})", HASH_ALGOS)).into()
         }
-        "sha1" => {
-            use sha1::{Digest, Sha1};
-            let mut hasher = Sha1::new();
-            hasher.update(text.as_bytes());
-            format!("{:x}", hasher.finalize())
+    };
+    // "HEX" is deliberately case-sensitive: it selects uppercase hex.
+    match output.unwrap_or("hex") {
+        "hex" => Reply::text(hex::encode(digest)),
+        "HEX" => Reply::text(hex::encode_upper(digest)),
+        "base64" => Reply::text(general_purpose::STANDARD.encode(digest)),
+        "base64url" => Reply::text(general_purpose::URL_SAFE_NO_PAD.encode(digest)),
+        _ => ApiError::unsupported("Unsupported output (supported: hex, HEX, base64, base64url)").into(),
+    }
+}
+
+/// Raw digest bytes for `algo`. Checksums (crc32, xxh*) are returned big-endian so
+/// their hex form matches the usual `crc32`/`xxhsum` output.
+fn digest_bytes(algo: &str, bytes: &[u8]) -> Option<Vec<u8>> {
+    fn d<D: Digest>(bytes: &[u8]) -> Vec<u8> {
+        D::digest(bytes).to_vec()
+    }
+    Some(match algo {
+        "md5" => md5::compute(bytes).0.to_vec(),
+        "sha1" => d::<sha1::Sha1>(bytes),
+        "sha224" => d::<Sha224>(bytes),
+        "sha256" => d::<Sha256>(bytes),
+        "sha384" => d::<Sha384>(bytes),
+        "sha512" => d::<Sha512>(bytes),
+        // `/` can't appear in a path segment, so SHA-512/256 is spelled sha512-256
+        "sha512-256" | "sha512_256" => d::<Sha512_256>(bytes),
+        "sha3-224" => d::<sha3::Sha3_224>(bytes),
+        "sha3-256" => d::<sha3::Sha3_256>(bytes),
+        "sha3-384" => d::<sha3::Sha3_384>(bytes),
+        "sha3-512" => d::<sha3::Sha3_512>(bytes),
+        "blake2b" => d::<blake2::Blake2b512>(bytes),
+        "blake2s" => d::<blake2::Blake2s256>(bytes),
+        "blake3" => blake3::hash(bytes).as_bytes().to_vec(),
+        "crc32" => crc32fast::hash(bytes).to_be_bytes().to_vec(),
+        "xxh32" => xxhash_rust::xxh32::xxh32(bytes, 0).to_be_bytes().to_vec(),
+        "xxh64" => xxhash_rust::xxh64::xxh64(bytes, 0).to_be_bytes().to_vec(),
+        "xxh3" => xxhash_rust::xxh3::xxh3_64(bytes).to_be_bytes().to_vec(),
+        _ => return None,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct HmacRequest {
+    key: String,
+    message: String,
+    /// How `key` is written: utf8 (default), hex or base64.
+    key_encoding: Option<String>,
+    /// When present, verify mode: compared in constant time against the computed MAC.
+    /// Hex (optionally prefixed like GitHub's `sha256=`) or base64.
+    signature: Option<String>,
+}
+
+/// `POST /hmac/sha256` with `{"key": "secret", "message": "payload"}` -> hex + base64 MAC.
+/// Add `"signature": "..."` to check a webhook signature instead.
+#[post("/hmac/<algo>", data = "<body>")]
+pub fn hmac_compute(_key: ApiKey, algo: String, body: Result<Json<HmacRequest>, json::Error<'_>>) -> Reply {
+    let req = match body {
+        Ok(req) => req.into_inner(),
+        Err(_) => {
+            return ApiError::bad_request("expected a JSON body like {\"key\": \"...\", \"message\": \"...\"}")
+                .into()
         }
-        "sha256" => {
-            let mut hasher = Sha256::new();
-            hasher.update(text.as_bytes());
-            format!("{:x}", hasher.finalize())
+    };
+    if req.message.len() > MAX_HASH_BODY || req.key.len() > MAX_HASH_BODY {
+        return ApiError::too_large("Input too large (max 256 kB)").into();
+    }
+
+    let key = match req.key_encoding.as_deref().unwrap_or("utf8") {
+        "utf8" => Ok(req.key.into_bytes()),
+        "hex" => hex::decode(req.key.trim()).map_err(|_| "key is not valid hex"),
+        "base64" => general_purpose::STANDARD.decode(req.key.trim()).map_err(|_| "key is not valid base64"),
+        _ => Err("unsupported key_encoding (utf8, hex, base64)"),
+    };
+    let key = match key {
+        Ok(k) => k,
+        Err(e) => return ApiError::invalid(e).into(),
+    };
+
+    let expected = match req.signature.as_deref().map(decode_signature) {
+        Some(Some(sig)) => Some(sig),
+        Some(None) => return ApiError::invalid("signature must be hex or base64").into(),
+        None => None,
+    };
+    let msg = req.message.as_bytes();
+    let expected = expected.as_deref();
+
+    let algo = algo.to_lowercase();
+    let (mac, valid) = match algo.as_str() {
+        "sha1" => compute_hmac::<Hmac<sha1::Sha1>>(&key, msg, expected),
+        "sha256" => compute_hmac::<Hmac<Sha256>>(&key, msg, expected),
+        "sha384" => compute_hmac::<Hmac<Sha384>>(&key, msg, expected),
+        "sha512" => compute_hmac::<Hmac<Sha512>>(&key, msg, expected),
+        _ => {
+            return ApiError::unsupported("Unsupported HMAC algorithm (supported: sha1, sha256, sha384, sha512)")
+                .into()
         }
-        _ => "Error: Unsupported hash algorithm (supported: md5, sha1, sha256)".to_string(),
+    };
+
+    let mut out = serde_json::json!({
+        "algorithm": format!("hmac-{}", algo),
+        "hex": hex::encode(&mac),
+        "base64": general_purpose::STANDARD.encode(&mac),
+    });
+    if let Some(valid) = valid {
+        out["valid"] = Value::Bool(valid);
     }
+    Reply::json(out)
+}
+
+fn compute_hmac<M: Mac + KeyInit + Clone>(
+    key: &[u8],
+    msg: &[u8],
+    expected: Option<&[u8]>,
+) -> (Vec<u8>, Option<bool>) {
+    // HMAC accepts keys of any length, so this can't fail.
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC takes any key size");
+    mac.update(msg);
+    // verify_slice compares in constant time
+    let valid = expected.map(|sig| mac.clone().verify_slice(sig).is_ok());
+    (mac.finalize().into_bytes().to_vec(), valid)
+}
+
+fn decode_signature(sig: &str) -> Option<Vec<u8>> {
+    let sig = sig.trim();
+    // Accept `sha256=<hex>` style prefixes used by GitHub and others.
+    let sig = sig.split_once('=').filter(|(p, _)| p.starts_with("sha")).map_or(sig, |(_, s)| s);
+    if sig.len().is_multiple_of(2) && sig.chars().all(|c| c.is_ascii_hexdigit()) {
+        return hex::decode(sig).ok();
+    }
+    general_purpose::STANDARD
+        .decode(sig)
+        .or_else(|_| general_purpose::URL_SAFE_NO_PAD.decode(sig.trim_end_matches('=')))
+        .ok()
 }
 
 // JWT decode endpoint
 #[get("/jwt-decode/<token>")]
-pub fn jwt_decode(token: String) -> Json<Value> {
+pub fn jwt_decode(_key: ApiKey, token: String) -> Reply {
     // SECURITY WARNING: This endpoint decodes JWT tokens for inspection only.
     // It does NOT verify signatures or validate claims. Never use this for authentication!
     
     // SECURITY FIX: Limit token size to prevent DoS attacks
     if token.len() > 8192 {
-        return Json(serde_json::json!({"error": "Token too large (max 8KB)"}));
+        return ApiError::too_large("Token too large (max 8KB)").into();
     }
     
     let parts: Vec<&str> = token.split('.').collect();
     
     if parts.len() != 3 {
-        return Json(serde_json::json!({"error": "Invalid JWT format"}));
+        return ApiError::invalid("Invalid JWT format").into();
     }
     
-    let decode_part = |part: &str| -> Result<Value, Box<dyn std::error::Error>> {
-        // Add padding if needed
-        let mut padded = part.to_string();
-        while padded.len() % 4 != 0 {
-            padded.push('=');
-        }
-        
-        let decoded = general_purpose::URL_SAFE_NO_PAD.decode(&padded.replace('-', "+").replace('_', "/"))?;
-        let json: Value = serde_json::from_slice(&decoded)?;
-        Ok(json)
-    };
-    
-    match (decode_part(parts[0]), decode_part(parts[1])) {
-        (Ok(header), Ok(payload)) => {
-            Json(serde_json::json!({
+    match (decode_segment(parts[0]), decode_segment(parts[1])) {
+        (Some(header), Some(payload)) => {
+            let claims = time_claims(&payload);
+            let status = |claim: &str| claims.get(claim).and_then(|c| c.get("status")).cloned();
+            let alg = header.get("alg").and_then(Value::as_str).unwrap_or("").to_string();
+            let mut out = serde_json::json!({
                 "warning": "This is for inspection only - signature NOT verified!",
+                "algorithm": alg,
+                "expired": status("exp") == Some(Value::from("expired")),
+                "not_yet_valid": status("nbf") == Some(Value::from("not_yet_valid")),
+                "claims": claims,
                 "header": header,
                 "payload": payload
-            }))
+            });
+            if alg.is_empty() || alg.eq_ignore_ascii_case("none") {
+                out["alg_warning"] = Value::from("alg is 'none' or missing: the token is unsigned and must never be trusted");
+            }
+            Reply::json(out)
+        }
+        _ => ApiError::invalid("Invalid JWT format or encoding").into(),
+    }
+}
+
+// Decode one base64url JWT segment into JSON. Tolerates stray `=` padding.
+fn decode_segment(part: &str) -> Option<Value> {
+    let decoded = general_purpose::URL_SAFE_NO_PAD.decode(part.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&decoded).ok()
+}
+
+/// Status of the registered time claims (`exp`, `nbf`, `iat`) relative to now, with
+/// ISO-8601 and relative times ("expires in 3h 12m", "expired 2d ago").
+fn time_claims(payload: &Value) -> Value {
+    let now = Utc::now().timestamp();
+    let mut claims = serde_json::Map::new();
+    for name in ["exp", "nbf", "iat"] {
+        let ts = match payload.get(name).and_then(Value::as_i64) {
+            Some(ts) => ts,
+            None => continue,
+        };
+        let delta = ts.saturating_sub(now);
+        let (status, relative) = match name {
+            "exp" if delta <= 0 => ("expired", format!("expired {} ago", approx(delta))),
+            "exp" => ("ok", format!("expires in {}", approx(delta))),
+            "nbf" if delta > 0 => ("not_yet_valid", format!("valid in {}", approx(delta))),
+            "nbf" => ("ok", format!("valid since {} ago", approx(delta))),
+            _ if delta > 0 => ("issued_in_future", format!("issued {} in the future", approx(delta))),
+            _ => ("ok", format!("issued {} ago", approx(delta))),
+        };
+        let utc = Utc
+            .timestamp_opt(ts, 0)
+            .single()
+            .map(|dt| dt.to_rfc3339());
+        claims.insert(name.to_string(), serde_json::json!({
+            "value": ts,
+            "utc": utc,
+            "relative": relative,
+            "status": status,
+        }));
+    }
+    Value::Object(claims)
+}
+
+// Two most significant units of time::humanize, e.g. "3h 12m".
+fn approx(seconds: i64) -> String {
+    humanize(seconds).split(' ').take(2).collect::<Vec<_>>().join(" ")
+}
+
+fn is_hmac(alg: Algorithm) -> bool {
+    matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+#[derive(Deserialize)]
+pub struct JwtVerifyRequest {
+    token: String,
+    /// Shared secret for HS256/HS384/HS512.
+    secret: Option<String>,
+    /// Public key for RS*/PS*/ES*/EdDSA: a PEM string, or a JWK (object or JSON string).
+    key: Option<Value>,
+}
+
+/// `POST /jwt/verify` with `{"token": "...", "secret": "..."}` or `{"token": "...", "key": <PEM or JWK>}`.
+/// Reports signature validity plus exp/nbf/iat status; the algorithm must match the key type.
+#[post("/jwt/verify", data = "<body>")]
+pub fn jwt_verify(_key: ApiKey, body: Result<Json<JwtVerifyRequest>, json::Error<'_>>) -> Reply {
+    let req = match body {
+        Ok(req) => req.into_inner(),
+        Err(_) => {
+            return ApiError::bad_request("expected a JSON body like {\"token\": \"...\", \"secret\": \"...\"}")
+                .into()
+        }
+    };
+    if req.token.len() > 8192 {
+        return ApiError::too_large("Token too large (max 8KB)").into();
+    }
+    let token = req.token.trim();
+
+    // alg=none and unknown algorithms fail here
+    let header = match jsonwebtoken::decode_header(token) {
+        Ok(h) => h,
+        Err(_) => return ApiError::invalid("Invalid JWT header or unsupported alg").into(),
+    };
+    let alg = header.alg;
+
+    // SECURITY: pick the key type from what the caller supplied, never from the token,
+    // so an HS256 token can't be "verified" with a public key used as an HMAC secret.
+    let key = match (&req.secret, &req.key) {
+        (Some(secret), None) if is_hmac(alg) => Ok(DecodingKey::from_secret(secret.as_bytes())),
+        (None, Some(key)) if !is_hmac(alg) => decoding_key(alg, key),
+        (Some(_), Some(_)) | (None, None) => Err("provide exactly one of secret or key".to_string()),
+        _ => Err(format!("{:?} tokens need a {}", alg, if is_hmac(alg) { "secret" } else { "public key" })),
+    };
+    let key = match key {
+        Ok(k) => k,
+        Err(e) => return ApiError::invalid(e).into(),
+    };
+
+    // Claims are reported rather than enforced, so only the signature decides `signature_valid`.
+    let mut validation = Validation::new(alg);
+    validation.validate_exp = false;
+    validation.validate_nbf = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+
+    let signature_valid = match jsonwebtoken::decode::<Value>(token, &key, &validation) {
+        Ok(_) => true,
+        Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => false,
+        Err(e) => return ApiError::invalid(format!("cannot verify token: {}", e)).into(),
+    };
+
+    let parts: Vec<&str> = token.split('.').collect();
+    let payload = parts.get(1).and_then(|p| decode_segment(p)).unwrap_or(Value::Null);
+    Reply::json(serde_json::json!({
+        "algorithm": format!("{:?}", alg),
+        "signature_valid": signature_valid,
+        "header": parts.first().and_then(|p| decode_segment(p)),
+        "claims": time_claims(&payload),
+        "payload": payload,
+    }))
+}
+
+fn decoding_key(alg: Algorithm, key: &Value) -> Result<DecodingKey, String> {
+    let text = match key {
+        Value::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    };
+    if text.starts_with('{') {
+        let jwk: Jwk = serde_json::from_str(&text).map_err(|_| "key is not a valid JWK".to_string())?;
+        return DecodingKey::from_jwk(&jwk).map_err(|e| format!("unusable JWK: {}", e));
+    }
+    let pem = text.as_bytes();
+    match alg {
+        Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem),
+        _ => DecodingKey::from_rsa_pem(pem),
+    }
+    .map_err(|_| format!("key is not a valid PEM public key for {:?}", alg))
+}
+
+#[derive(Deserialize)]
+pub struct JwtSignRequest {
+    /// Optional header fields (alg, typ, kid, cty...). `alg` defaults to HS256.
+    header: Option<Value>,
+    payload: Value,
+    /// Shared secret for HS256/HS384/HS512.
+    secret: Option<String>,
+    /// PKCS#8 private key PEM for RS*/PS*/ES*/EdDSA.
+    key: Option<String>,
+}
+
+/// `POST /jwt/sign` with `{"payload": {...}, "secret": "..."}` -> a signed token, for minting test tokens.
+#[post("/jwt/sign", data = "<body>")]
+pub fn jwt_sign(_key: ApiKey, body: Result<Json<JwtSignRequest>, json::Error<'_>>) -> Reply {
+    let req = match body {
+        Ok(req) => req.into_inner(),
+        Err(_) => {
+            return ApiError::bad_request("expected a JSON body like {\"payload\": {...}, \"secret\": \"...\"}")
+                .into()
         }
-        _ => Json(serde_json::json!({"error": "Invalid JWT format or encoding"})),
+    };
+    if !req.payload.is_object() {
+        return ApiError::invalid("payload must be a JSON object").into();
+    }
+
+    let mut header_json = req.header.unwrap_or_else(|| serde_json::json!({}));
+    if !header_json.is_object() {
+        return ApiError::invalid("header must be a JSON object").into();
+    }
+    if header_json.get("alg").is_none() {
+        header_json["alg"] = Value::from("HS256");
+    }
+    let header: Header = match serde_json::from_value(header_json) {
+        Ok(h) => h,
+        Err(_) => return ApiError::unsupported("unsupported alg or invalid header").into(),
+    };
+    let alg = header.alg;
+
+    let key = match (&req.secret, &req.key) {
+        (Some(secret), None) if is_hmac(alg) => Ok(EncodingKey::from_secret(secret.as_bytes())),
+        (None, Some(pem)) if !is_hmac(alg) => {
+            let pem = pem.trim().as_bytes();
+            match alg {
+                Algorithm::ES256 | Algorithm::ES384 => EncodingKey::from_ec_pem(pem),
+                Algorithm::EdDSA => EncodingKey::from_ed_pem(pem),
+                _ => EncodingKey::from_rsa_pem(pem),
+            }
+            .map_err(|_| format!("key is not a valid PEM private key for {:?}", alg))
+        }
+        (Some(_), Some(_)) | (None, None) => Err("provide exactly one of secret or key".to_string()),
+        _ => Err(format!("{:?} needs a {}", alg, if is_hmac(alg) { "secret" } else { "private key" })),
+    };
+    let key = match key {
+        Ok(k) => k,
+        Err(e) => return ApiError::invalid(e).into(),
+    };
+
+    match jsonwebtoken::encode(&header, &req.payload, &key) {
+        Ok(token) => Reply::json(serde_json::json!({
+            "algorithm": format!("{:?}", alg),
+            "token": token,
+        })),
+        Err(e) => ApiError::invalid(format!("cannot sign token: {}", e)).into(),
     }
 }
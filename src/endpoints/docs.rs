@@ -0,0 +1,17 @@
+use rocket::State;
+use rocket_dyn_templates::{context, Template};
+
+use crate::openapi::ApiSpec;
+use crate::response::Reply;
+
+/// OpenAPI 3 description of every mounted route. `?format=yaml` works too.
+#[get("/openapi.json")]
+pub fn openapi_json(spec: &State<ApiSpec>) -> Reply {
+    Reply::json(&spec.0)
+}
+
+/// API reference page rendered client-side from `/openapi.json`; no external assets.
+#[get("/docs")]
+pub fn docs() -> Template {
+    Template::render("docs", context! {})
+}
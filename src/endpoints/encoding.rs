@@ -1,36 +1,44 @@
 use base64::{Engine as _, engine::general_purpose};
+use crate::auth::ApiKey;
+use crate::extractors::BodyInput;
+use crate::response::{ApiError, Reply};
 
 // Encoding endpoints - SECURITY FIX: Updated to current base64 API v0.21
 #[get("/base64/<text>")]
-pub fn base64_encode(text: String) -> String {
+pub fn base64_encode(_key: ApiKey, text: String) -> Reply {
     // SECURITY FIX: Limit input size to prevent DoS attacks
     if text.len() > 1_000_000 {
-        return "Error: Input too large (max 1MB)".to_string();
+        return ApiError::too_large("Input too large (max 1MB)").into();
     }
-    general_purpose::STANDARD.encode(text.as_bytes())
+    Reply::text(general_purpose::STANDARD.encode(text.as_bytes()))
 }
 
-#[get("/base64-decode/<b64>")]
-pub fn base64_decode(b64: String) -> String {
+#[get("/base64-decode/<b64>?<raw>")]
+pub fn base64_decode(_key: ApiKey, b64: String, raw: Option<bool>) -> Reply {
     // SECURITY FIX: Limit input size to prevent DoS attacks
     if b64.len() > 1_500_000 {
-        return "Error: Input too large (max ~1.5MB base64)".to_string();
+        return ApiError::too_large("Input too large (max ~1.5MB base64)").into();
     }
     match general_purpose::STANDARD.decode(&b64) {
-        Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-        Err(_) => "Error: Invalid base64 encoding".to_string(),
+        Ok(bytes) => decoded(bytes, raw),
+        Err(_) => ApiError::invalid("Invalid base64 encoding").into(),
     }
 }
 
 #[get("/urlencode/<text>")]
-pub fn url_encode(text: String) -> String {
+pub fn url_encode(_key: ApiKey, text: String) -> Reply {
     // SECURITY FIX: Limit input size to prevent DoS attacks
     if text.len() > 100_000 {
-        return "Error: Input too large (max 100KB)".to_string();
+        return ApiError::too_large("Input too large (max 100KB)").into();
     }
-    // Proper URL encoding that handles Unicode correctly
-    text.bytes()
-        .map(|b| match b {
+    Reply::text(percent_encode(text.as_bytes()))
+}
+
+// Proper URL encoding that handles Unicode correctly
+pub fn percent_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
             b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
                 (b as char).to_string()
             }
@@ -40,13 +48,17 @@ pub fn url_encode(text: String) -> String {
         .collect()
 }
 
-#[get("/urldecode/<encoded>")]
-pub fn url_decode(encoded: String) -> String {
+#[get("/urldecode/<encoded>?<raw>")]
+pub fn url_decode(_key: ApiKey, encoded: String, raw: Option<bool>) -> Reply {
     // SECURITY FIX: Limit input size to prevent DoS attacks
     if encoded.len() > 100_000 {
-        return "Error: Input too large (max 100KB)".to_string();
+        return ApiError::too_large("Input too large (max 100KB)").into();
     }
-    // SECURITY FIX: Safe URL decoding with proper UTF-8 validation
+    decoded(percent_decode(&encoded), raw)
+}
+
+// SECURITY FIX: Safe URL decoding; invalid sequences are kept as literal text
+fn percent_decode(encoded: &str) -> Vec<u8> {
     let mut bytes = Vec::new();
     let mut chars = encoded.chars().peekable();
 
@@ -72,41 +84,44 @@ pub fn url_decode(encoded: String) -> String {
         }
     }
 
-    // SECURITY FIX: Use from_utf8_lossy for safe UTF-8 conversion
-    String::from_utf8_lossy(&bytes).to_string()
+    bytes
 }
 
 // ---- Additional encoders (query-param based) ----
 
-const MAX_ENC: usize = 100_000;
+pub const MAX_ENC: usize = 100_000;
 
 /// `/hex/encode?text=hi` -> "6869"
 #[get("/hex/encode?<text>")]
-pub fn hex_encode(text: String) -> String {
+pub fn hex_encode(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_ENC {
-        return "Error: input too large (max 100KB)".to_string();
+        return ApiError::too_large("input too large (max 100KB)").into();
     }
-    hex::encode(text.as_bytes())
+    Reply::text(hex::encode(text.as_bytes()))
 }
 
 /// `/hex/decode?input=6869` -> "hi"
-#[get("/hex/decode?<input>")]
-pub fn hex_decode(input: String) -> String {
+#[get("/hex/decode?<input>&<raw>")]
+pub fn hex_decode(_key: ApiKey, input: String, raw: Option<bool>) -> Reply {
     if input.len() > 2 * MAX_ENC {
-        return "Error: input too large".to_string();
+        return ApiError::too_large("input too large").into();
     }
     match hex::decode(input.trim()) {
-        Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-        Err(_) => "Error: invalid hex".to_string(),
+        Ok(bytes) => decoded(bytes, raw),
+        Err(_) => ApiError::invalid("invalid hex").into(),
     }
 }
 
 /// `/rot13?text=Hello` -> "Uryyb" (self-inverse)
 #[get("/rot13?<text>")]
-pub fn rot13(text: String) -> String {
+pub fn rot13(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_ENC {
-        return "Error: input too large".to_string();
+        return ApiError::too_large("input too large").into();
     }
+    Reply::text(rot13_str(&text))
+}
+
+fn rot13_str(text: &str) -> String {
     text.chars()
         .map(|c| match c {
             'a'..='z' => (((c as u8 - b'a' + 13) % 26) + b'a') as char,
@@ -118,10 +133,14 @@ pub fn rot13(text: String) -> String {
 
 /// `/html/encode?text=<b>` -> "&lt;b&gt;"
 #[get("/html/encode?<text>")]
-pub fn html_encode(text: String) -> String {
+pub fn html_encode(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_ENC {
-        return "Error: input too large".to_string();
+        return ApiError::too_large("input too large").into();
     }
+    Reply::text(html_escape(&text))
+}
+
+fn html_escape(text: &str) -> String {
     text.chars()
         .map(|c| match c {
             '&' => "&amp;".to_string(),
@@ -136,10 +155,14 @@ pub fn html_encode(text: String) -> String {
 
 /// `/html/decode?text=&lt;b&gt;` -> "<b>"
 #[get("/html/decode?<text>")]
-pub fn html_decode(text: String) -> String {
+pub fn html_decode(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_ENC {
-        return "Error: input too large".to_string();
+        return ApiError::too_large("input too large").into();
     }
+    Reply::text(html_unescape(&text))
+}
+
+fn html_unescape(text: &str) -> String {
     // Decode named entities first, then &amp; LAST to avoid double-unescaping.
     let s = text
         .replace("&lt;", "<")
@@ -152,21 +175,145 @@ pub fn html_decode(text: String) -> String {
 
 /// `/base64url/encode?text=hello` -> URL-safe base64 without padding
 #[get("/base64url/encode?<text>")]
-pub fn base64url_encode(text: String) -> String {
+pub fn base64url_encode(_key: ApiKey, text: String) -> Reply {
     if text.len() > 1_000_000 {
-        return "Error: input too large (max 1MB)".to_string();
+        return ApiError::too_large("input too large (max 1MB)").into();
     }
-    general_purpose::URL_SAFE_NO_PAD.encode(text.as_bytes())
+    Reply::text(general_purpose::URL_SAFE_NO_PAD.encode(text.as_bytes()))
 }
 
 /// `/base64url/decode?input=...`
-#[get("/base64url/decode?<input>")]
-pub fn base64url_decode(input: String) -> String {
+#[get("/base64url/decode?<input>&<raw>")]
+pub fn base64url_decode(_key: ApiKey, input: String, raw: Option<bool>) -> Reply {
     if input.len() > 1_500_000 {
-        return "Error: input too large".to_string();
+        return ApiError::too_large("input too large").into();
     }
     match general_purpose::URL_SAFE_NO_PAD.decode(input.trim()) {
-        Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-        Err(_) => "Error: invalid base64url".to_string(),
+        Ok(bytes) => decoded(bytes, raw),
+        Err(_) => ApiError::invalid("invalid base64url").into(),
+    }
+}
+
+/// Decoded bytes as lossy UTF-8 text, or untouched as `application/octet-stream`
+/// when `?raw=true` is passed (for binary payloads).
+fn decoded(bytes: Vec<u8>, raw: Option<bool>) -> Reply {
+    if raw.unwrap_or(false) {
+        Reply::bytes(bytes)
+    } else {
+        Reply::text(String::from_utf8_lossy(&bytes))
+    }
+}
+
+// ---- POST variants: raw body bytes or {"input": ...} JSON (see `BodyInput`) ----
+
+/// Unwrap a POST body, applying the same per-endpoint cap as the GET variant.
+fn body_bytes(body: Result<BodyInput, ApiError>, max: usize) -> Result<Vec<u8>, ApiError> {
+    let bytes = body?.0;
+    if bytes.len() > max {
+        return Err(ApiError::too_large("input too large"));
+    }
+    Ok(bytes)
+}
+
+/// Like `body_bytes`, for decoders whose input must be text.
+fn body_text(body: Result<BodyInput, ApiError>, max: usize) -> Result<String, ApiError> {
+    String::from_utf8(body_bytes(body, max)?).map_err(|_| ApiError::invalid("input must be UTF-8 text"))
+}
+
+#[post("/base64", data = "<body>")]
+pub fn base64_encode_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_bytes(body, 1_000_000) {
+        Ok(bytes) => Reply::text(general_purpose::STANDARD.encode(bytes)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/base64-decode?<raw>", data = "<body>")]
+pub fn base64_decode_body(_key: ApiKey, body: Result<BodyInput, ApiError>, raw: Option<bool>) -> Reply {
+    match body_text(body, 1_500_000) {
+        // Tolerate line-wrapped input (e.g. `base64` CLI output)
+        Ok(text) => match general_purpose::STANDARD.decode(text.split_whitespace().collect::<String>()) {
+            Ok(bytes) => decoded(bytes, raw),
+            Err(_) => ApiError::invalid("Invalid base64 encoding").into(),
+        },
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/urlencode", data = "<body>")]
+pub fn url_encode_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_bytes(body, MAX_ENC) {
+        Ok(bytes) => Reply::text(percent_encode(&bytes)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/urldecode?<raw>", data = "<body>")]
+pub fn url_decode_body(_key: ApiKey, body: Result<BodyInput, ApiError>, raw: Option<bool>) -> Reply {
+    match body_text(body, MAX_ENC) {
+        Ok(text) => decoded(percent_decode(&text), raw),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/hex/encode", data = "<body>")]
+pub fn hex_encode_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_bytes(body, MAX_ENC) {
+        Ok(bytes) => Reply::text(hex::encode(bytes)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/hex/decode?<raw>", data = "<body>")]
+pub fn hex_decode_body(_key: ApiKey, body: Result<BodyInput, ApiError>, raw: Option<bool>) -> Reply {
+    match body_text(body, 2 * MAX_ENC) {
+        Ok(text) => match hex::decode(text.split_whitespace().collect::<String>()) {
+            Ok(bytes) => decoded(bytes, raw),
+            Err(_) => ApiError::invalid("invalid hex").into(),
+        },
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/rot13", data = "<body>")]
+pub fn rot13_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_text(body, MAX_ENC) {
+        Ok(text) => Reply::text(rot13_str(&text)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/html/encode", data = "<body>")]
+pub fn html_encode_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_text(body, MAX_ENC) {
+        Ok(text) => Reply::text(html_escape(&text)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/html/decode", data = "<body>")]
+pub fn html_decode_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_text(body, MAX_ENC) {
+        Ok(text) => Reply::text(html_unescape(&text)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/base64url/encode", data = "<body>")]
+pub fn base64url_encode_body(_key: ApiKey, body: Result<BodyInput, ApiError>) -> Reply {
+    match body_bytes(body, 1_000_000) {
+        Ok(bytes) => Reply::text(general_purpose::URL_SAFE_NO_PAD.encode(bytes)),
+        Err(e) => e.into(),
+    }
+}
+
+#[post("/base64url/decode?<raw>", data = "<body>")]
+pub fn base64url_decode_body(_key: ApiKey, body: Result<BodyInput, ApiError>, raw: Option<bool>) -> Reply {
+    match body_text(body, 1_500_000) {
+        Ok(text) => match general_purpose::URL_SAFE_NO_PAD.decode(text.trim()) {
+            Ok(bytes) => decoded(bytes, raw),
+            Err(_) => ApiError::invalid("invalid base64url").into(),
+        },
+        Err(e) => e.into(),
     }
 }
@@ -1,30 +1,32 @@
 use rand::Rng;
-use rocket::serde::json::{serde_json, Json, Value};
+use rocket::serde::json::serde_json;
 
+use crate::auth::ApiKey;
 use crate::constants::{CAT_FACTS, EIGHT_BALL, QUOTES};
+use crate::response::{ApiError, Reply};
 
 // Fun endpoints
 #[get("/cat-fact")]
-pub fn cat_fact() -> String {
+pub fn cat_fact(_key: ApiKey) -> Reply {
     let mut rng = rand::thread_rng();
     let index = rng.gen_range(0..CAT_FACTS.len());
-    CAT_FACTS[index].to_string()
+    Reply::text(CAT_FACTS[index])
 }
 
 #[get("/quote")]
-pub fn quote() -> String {
+pub fn quote(_key: ApiKey) -> Reply {
     let mut rng = rand::thread_rng();
     let index = rng.gen_range(0..QUOTES.len());
-    QUOTES[index].to_string()
+    Reply::text(QUOTES[index])
 }
 
 /// Roll dice in `NdM` notation, e.g. `/roll/2d6`. Capped at 100 dice / 1000 sides.
 #[get("/roll/<dice>")]
-pub fn roll(dice: String) -> Json<Value> {
+pub fn roll(_key: ApiKey, dice: String) -> Reply {
     let lower = dice.to_lowercase();
     let parts: Vec<&str> = lower.split('d').collect();
     if parts.len() != 2 {
-        return Json(serde_json::json!({"error": "format: NdM, e.g. 2d6"}));
+        return ApiError::bad_request("format: NdM, e.g. 2d6").into();
     }
 
     let count: u32 = if parts[0].is_empty() {
@@ -32,26 +34,26 @@ pub fn roll(dice: String) -> Json<Value> {
     } else {
         match parts[0].parse() {
             Ok(v) => v,
-            Err(_) => return Json(serde_json::json!({"error": "invalid dice count"})),
+            Err(_) => return ApiError::invalid("invalid dice count").into(),
         }
     };
     let sides: u32 = match parts[1].parse() {
         Ok(v) => v,
-        Err(_) => return Json(serde_json::json!({"error": "invalid number of sides"})),
+        Err(_) => return ApiError::invalid("invalid number of sides").into(),
     };
 
     if !(1..=100).contains(&count) {
-        return Json(serde_json::json!({"error": "dice count must be 1-100"}));
+        return ApiError::invalid("dice count must be 1-100").into();
     }
     if !(2..=1000).contains(&sides) {
-        return Json(serde_json::json!({"error": "sides must be 2-1000"}));
+        return ApiError::invalid("sides must be 2-1000").into();
     }
 
     let mut rng = rand::thread_rng();
     let rolls: Vec<u32> = (0..count).map(|_| rng.gen_range(1..=sides)).collect();
     let total: u64 = rolls.iter().map(|&r| r as u64).sum();
 
-    Json(serde_json::json!({
+    Reply::json(serde_json::json!({
         "dice": dice,
         "rolls": rolls,
         "total": total,
@@ -59,25 +61,25 @@ pub fn roll(dice: String) -> Json<Value> {
 }
 
 #[get("/coinflip")]
-pub fn coinflip() -> Json<Value> {
+pub fn coinflip(_key: ApiKey) -> Reply {
     let mut rng = rand::thread_rng();
-    Json(serde_json::json!({
+    Reply::json(serde_json::json!({
         "result": if rng.gen::<bool>() { "heads" } else { "tails" }
     }))
 }
 
 #[get("/8ball")]
-pub fn eight_ball() -> Json<Value> {
+pub fn eight_ball(_key: ApiKey) -> Reply {
     let mut rng = rand::thread_rng();
     let index = rng.gen_range(0..EIGHT_BALL.len());
-    Json(serde_json::json!({"answer": EIGHT_BALL[index]}))
+    Reply::json(serde_json::json!({"answer": EIGHT_BALL[index]}))
 }
 
 /// Pick a random option. `/pick?options=red,green,blue`
 #[get("/pick?<options>")]
-pub fn pick(options: String) -> Json<Value> {
+pub fn pick(_key: ApiKey, options: String) -> Reply {
     if options.len() > 10_000 {
-        return Json(serde_json::json!({"error": "input too large"}));
+        return ApiError::too_large("input too large").into();
     }
     let opts: Vec<&str> = options
         .split(',')
@@ -85,11 +87,11 @@ pub fn pick(options: String) -> Json<Value> {
         .filter(|s| !s.is_empty())
         .collect();
     if opts.is_empty() {
-        return Json(serde_json::json!({"error": "provide comma-separated options, e.g. a,b,c"}));
+        return ApiError::bad_request("provide comma-separated options, e.g. a,b,c").into();
     }
     let mut rng = rand::thread_rng();
     let index = rng.gen_range(0..opts.len());
-    Json(serde_json::json!({
+    Reply::json(serde_json::json!({
         "options": opts,
         "chosen": opts[index],
     }))
@@ -1,20 +1,22 @@
 use std::cmp;
 use rand::Rng;
 use uuid::Uuid;
+use crate::auth::ApiKey;
 use crate::constants::LOREM_WORDS;
+use crate::response::{ApiError, Reply};
 
 #[get("/uuid")]
-pub fn generate_uuid() -> String {
-    Uuid::new_v4().to_string()
+pub fn generate_uuid(_key: ApiKey) -> Reply {
+    Reply::text(Uuid::new_v4().to_string())
 }
 
 // Generator endpoints - PERFORMANCE: Using static array
 #[get("/lorem/<words>")]
-pub fn lorem(words: usize) -> String {
+pub fn lorem(_key: ApiKey, words: usize) -> Reply {
     // Limit words to prevent resource exhaustion
     let safe_words = cmp::min(words, 1000); // Max 1000 words
     if safe_words == 0 {
-        return "Error: Word count must be greater than 0".to_string();
+        return ApiError::invalid("Word count must be greater than 0").into();
     }
     
     let mut rng = rand::thread_rng();
@@ -25,47 +27,48 @@ pub fn lorem(words: usize) -> String {
         result.push(LOREM_WORDS[word_index]);
     }
     
-    result.join(" ")
+    Reply::text(result.join(" "))
 }
 
 #[get("/color")]
-pub fn random_color() -> String {
+pub fn random_color(_key: ApiKey) -> Reply {
     let mut rng = rand::thread_rng();
-    format!("#{:06x}", rng.gen::<u32>() & 0xFFFFFF)
+    Reply::text(format!("#{:06x}", rng.gen::<u32>() & 0xFFFFFF))
 }
 
 #[get("/password/<length>")]
-pub fn generate_password(length: usize) -> String {
+pub fn generate_password(_key: ApiKey, length: usize) -> Reply {
     // Limit password length to prevent resource exhaustion
     let safe_length = cmp::min(length, 128); // Max 128 characters
     if safe_length == 0 {
-        return "Error: Password length must be greater than 0".to_string();
+        return ApiError::invalid("Password length must be greater than 0").into();
     }
     
     let charset = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%^&*";
     let mut rng = rand::thread_rng();
     
-    (0..safe_length)
+    let password: String = (0..safe_length)
         .map(|_| {
             let idx = rng.gen_range(0..charset.len());
             charset.chars().nth(idx).unwrap()
         })
-        .collect()
+        .collect();
+    Reply::text(password)
 }
 
 #[get("/number/<min>/<max>")]
-pub fn random_number(min: i32, max: i32) -> String {
+pub fn random_number(_key: ApiKey, min: i32, max: i32) -> Reply {
     // SECURITY FIX: Validate input ranges to prevent panic
     if min > max {
-        return "Error: min value cannot be greater than max value".to_string();
+        return ApiError::invalid("min value cannot be greater than max value").into();
     }
     
     // Prevent extreme ranges that could cause performance issues
     let range_size = (max as i64) - (min as i64);
     if range_size > 1_000_000_000 {
-        return "Error: range too large (max 1 billion)".to_string();
+        return ApiError::invalid("range too large (max 1 billion)").into();
     }
     
     let mut rng = rand::thread_rng();
-    rng.gen_range(min..=max).to_string()
+    Reply::text(rng.gen_range(min..=max).to_string())
 }
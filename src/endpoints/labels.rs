@@ -0,0 +1,151 @@
+use qrcode::EcLevel;
+use rocket::http::ContentType;
+use rocket::serde::json::{self, Json};
+use rocket::serde::Deserialize;
+
+use datamatrix::SymbolList;
+
+use crate::auth::ApiKey;
+use crate::aztec;
+use crate::barcode::{Symbology, SYMBOLOGIES};
+use crate::endpoints::qr::{aztec_modules, data_matrix_modules, qr_code, qr_modules, Image, BAR_MARGIN};
+use crate::sheet::{self, Label, Sheet};
+use crate::raster::Modules;
+use crate::response::ApiError;
+
+// Label sheets: one code per label on an A4 or Letter grid, laid out by `crate::sheet`
+// as an SVG page or a PDF.
+
+// SECURITY: a sheet holds many codes at once, so each gets less input than `/qr` etc.
+pub const MAX_LABELS: usize = 200;
+pub const MAX_LABEL_TEXT: usize = 200;
+pub const MAX_LABEL_GRID: usize = 20;
+
+/// Smallest label cell either way, in mm.
+const MIN_LABEL_MM: f64 = 10.0;
+
+/// Request body for `/labels`.
+#[derive(Deserialize)]
+pub struct LabelsRequest {
+    items: Vec<LabelItem>,
+    #[serde(default)]
+    layout: LabelLayout,
+}
+
+#[derive(Deserialize)]
+pub struct LabelItem {
+    text: String,
+    /// qr (default), datamatrix, aztec or any `/barcode` symbology.
+    symbology: Option<String>,
+    /// Printed under the code; 1D codes default to their human-readable text.
+    caption: Option<String>,
+}
+
+/// Page grid; lengths in millimetres.
+#[derive(Deserialize, Default)]
+pub struct LabelLayout {
+    page: Option<String>,
+    rows: Option<usize>,
+    cols: Option<usize>,
+    margin: Option<f64>,
+    gap: Option<f64>,
+}
+
+/// `POST /labels` with `{"items": [{"text": "A-001", "caption": "Asset 1"}], "layout": {"page":
+/// "a4", "rows": 8, "cols": 3}}` -> a sheet of codes as one SVG page, or as a PDF with as
+/// many pages as the items need (`?format=pdf`).
+#[post("/labels?<format>", data = "<body>")]
+pub fn labels(
+    _key: ApiKey,
+    format: Option<String>,
+    body: Result<Json<LabelsRequest>, json::Error<'_>>,
+) -> Result<Image, ApiError> {
+    let pdf = match format.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("svg") => false,
+        Some("pdf") => true,
+        Some(_) => return Err(ApiError::unsupported("unknown format (svg, pdf)")),
+    };
+    let req = body
+        .map_err(|_| ApiError::bad_request("expected a JSON body like {\"items\": [{\"text\": \"...\"}], \"layout\": {...}}"))?
+        .into_inner();
+    if req.items.is_empty() {
+        return Err(ApiError::bad_request("items is empty"));
+    }
+    if req.items.len() > MAX_LABELS {
+        return Err(ApiError::too_large(format!("too many items (max {})", MAX_LABELS)));
+    }
+    let layout = req.layout;
+    let page = sheet::page_size(layout.page.as_deref().unwrap_or("a4"))
+        .ok_or_else(|| ApiError::unsupported("unknown page (a4, letter)"))?;
+    let (rows, cols) = (layout.rows.unwrap_or(8), layout.cols.unwrap_or(3));
+    if !(1..=MAX_LABEL_GRID).contains(&rows) || !(1..=MAX_LABEL_GRID).contains(&cols) {
+        return Err(ApiError::invalid(format!("rows and cols must be 1-{}", MAX_LABEL_GRID)));
+    }
+    let margin = layout.margin.unwrap_or(10.0);
+    let gap = layout.gap.unwrap_or(2.0);
+    if !(0.0..=50.0).contains(&margin) || !(0.0..=20.0).contains(&gap) {
+        return Err(ApiError::invalid("margin must be 0-50 mm and gap 0-20 mm"));
+    }
+    let sheet = Sheet { page, rows, cols, margin, gap };
+    let (w, h) = sheet.cell();
+    if w < MIN_LABEL_MM || h < MIN_LABEL_MM {
+        return Err(ApiError::invalid(format!(
+            "labels would be {:.1} x {:.1} mm (min {} mm); use fewer rows or cols or smaller margins",
+            w, h, MIN_LABEL_MM
+        )));
+    }
+    let pages = sheet::pages(&sheet, req.items.len());
+    if !pdf && pages > 1 {
+        return Err(ApiError::invalid(format!(
+            "{} items need {} pages at {} per page; SVG holds one, use ?format=pdf",
+            req.items.len(),
+            pages,
+            sheet.per_page()
+        )));
+    }
+    let codes = req
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| label(item).map_err(|e| e.prefixed(&format!("items[{}]", i))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(if pdf {
+        (ContentType::PDF, sheet::to_pdf(&sheet, &codes))
+    } else {
+        (ContentType::SVG, sheet::to_svg(&sheet, &codes).into_bytes())
+    })
+}
+
+/// One label's code with its symbology's usual quiet zone.
+fn label(item: &LabelItem) -> Result<Label, ApiError> {
+    if item.text.is_empty() {
+        return Err(ApiError::bad_request("text is empty"));
+    }
+    if item.text.len() > MAX_LABEL_TEXT {
+        return Err(ApiError::too_large(format!("text too large (max {} chars)", MAX_LABEL_TEXT)));
+    }
+    let caption = item.caption.clone();
+    let symbology = item.symbology.as_deref().unwrap_or("qr").to_lowercase();
+    let (modules, linear, caption) = match symbology.as_str() {
+        "qr" => (qr_modules(&qr_code(&item.text, EcLevel::M, None)?).with_quiet_zone(4, 4), false, caption),
+        "datamatrix" | "data-matrix" => {
+            let modules = data_matrix_modules(item.text.as_bytes(), false, SymbolList::default())
+                .ok_or_else(|| ApiError::too_large("input exceeds Data Matrix capacity"))?;
+            (modules.with_quiet_zone(1, 1), false, caption)
+        }
+        "aztec" => {
+            let modules = aztec_modules(&item.text, aztec::DEFAULT_ECC_PERCENT, None, None)?;
+            (modules.with_quiet_zone(1, 1), false, caption)
+        }
+        other => {
+            let symbology: Symbology = other
+                .parse()
+                .map_err(|_| ApiError::unsupported(format!("unknown symbology (qr, datamatrix, aztec, {})", SYMBOLOGIES)))?;
+            let encoded = symbology.encode(&item.text).map_err(ApiError::invalid)?;
+            let bars = encoded.bars;
+            let modules = Modules::from_fn(bars.len(), 1, |x, _| bars[x] == 1).with_quiet_zone(BAR_MARGIN, 0);
+            (modules, true, caption.or(Some(encoded.text)))
+        }
+    };
+    Ok(Label { modules, linear, caption })
+}
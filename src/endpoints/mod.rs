@@ -1,10 +1,14 @@
 pub mod basic;
 pub mod convert;
 pub mod crypto;
+pub mod docs;
 pub mod encoding;
 pub mod fun;
 pub mod generators;
+pub mod labels;
+pub mod payloads;
 pub mod qr;
+pub mod scan;
 pub mod text;
 pub mod time;
 pub mod utils;
@@ -0,0 +1,292 @@
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime, Utc};
+
+use crate::auth::ApiKey;
+use crate::endpoints::encoding::percent_encode;
+use crate::endpoints::qr::{render_qr, Image, QrOptions};
+use crate::response::ApiError;
+
+// Structured QR payloads. Each endpoint builds the de-facto standard string for its
+// content type, escaped the way scanners expect, then renders it like `/qr` (same
+// options). `?format=json` returns the built payload instead of an image.
+
+/// `/qr/wifi?ssid=Home&password=secret` -> `WIFI:T:WPA;S:Home;P:secret;;`
+/// encryption: WPA (default with a password; covers WPA2), SAE (WPA3), WEP, nopass.
+#[get("/qr/wifi?<ssid>&<password>&<encryption>&<hidden>&<opts..>")]
+pub fn wifi(
+    _key: ApiKey,
+    ssid: String,
+    password: Option<String>,
+    encryption: Option<String>,
+    hidden: Option<bool>,
+    opts: QrOptions,
+) -> Result<Image, ApiError> {
+    render_qr(&wifi_payload(&ssid, password.as_deref(), encryption.as_deref(), hidden.unwrap_or(false))?, opts)
+}
+
+fn wifi_payload(ssid: &str, password: Option<&str>, encryption: Option<&str>, hidden: bool) -> Result<String, ApiError> {
+    if ssid.is_empty() {
+        return Err(ApiError::bad_request("provide ?ssid=..."));
+    }
+    let password = password.filter(|p| !p.is_empty());
+    let kind = match encryption.map(str::to_lowercase).as_deref() {
+        None if password.is_some() => "WPA",
+        None | Some("nopass") | Some("none") => "nopass",
+        Some("wpa") | Some("wpa2") => "WPA",
+        Some("sae") | Some("wpa3") => "SAE",
+        Some("wep") => "WEP",
+        Some(_) => return Err(ApiError::unsupported("unknown encryption (WPA, SAE, WEP, nopass)")),
+    };
+    let mut payload = format!("WIFI:T:{};S:{};", kind, wifi_escape(ssid));
+    match (kind, password) {
+        ("nopass", None) => {}
+        ("nopass", Some(_)) => return Err(ApiError::invalid("an open (nopass) network takes no password")),
+        (_, Some(p)) => payload.push_str(&format!("P:{};", wifi_escape(p))),
+        (_, None) => return Err(ApiError::bad_request(format!("{} networks need ?password=...", kind))),
+    }
+    if hidden {
+        payload.push_str("H:true;");
+    }
+    payload.push(';');
+    Ok(payload)
+}
+
+/// Backslash before `\ ; , : "`, per the ZXing Wi-Fi format.
+fn wifi_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// `/qr/vcard?name=Ada%20Lovelace&phone=...` -> a vCard 3.0 contact.
+#[get("/qr/vcard?<name>&<org>&<title>&<phone>&<email>&<url>&<address>&<note>&<opts..>")]
+#[allow(clippy::too_many_arguments)]
+pub fn vcard(
+    _key: ApiKey,
+    name: String,
+    org: Option<String>,
+    title: Option<String>,
+    phone: Option<String>,
+    email: Option<String>,
+    url: Option<String>,
+    address: Option<String>,
+    note: Option<String>,
+    opts: QrOptions,
+) -> Result<Image, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("provide ?name=..."));
+    }
+    // N is "family;given": take the last word as the family name.
+    let (given, family) = name.rsplit_once(char::is_whitespace).unwrap_or(("", name));
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:3.0".to_string(),
+        format!("N:{};{};;;", vcard_escape(family), vcard_escape(given.trim())),
+        format!("FN:{}", vcard_escape(name)),
+    ];
+    let fields = [
+        ("ORG", org),
+        ("TITLE", title),
+        ("TEL;TYPE=CELL", phone),
+        ("EMAIL", email),
+        ("URL", url),
+        ("NOTE", note),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            lines.push(format!("{}:{}", key, vcard_escape(value.trim())));
+        }
+    }
+    if let Some(address) = address.filter(|a| !a.trim().is_empty()) {
+        // Whole address as the street component; scanners show it as written.
+        lines.push(format!("ADR:;;{};;;;", vcard_escape(address.trim())));
+    }
+    lines.push("END:VCARD".to_string());
+    render_qr(&lines.join("\r\n"), opts)
+}
+
+/// TEXT escaping shared by vCard (RFC 2426) and iCalendar (RFC 5545).
+fn vcard_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// `/qr/geo?lat=25.2&lng=55.27` -> `geo:25.2,55.27` (RFC 5870).
+#[get("/qr/geo?<lat>&<lng>&<alt>&<opts..>")]
+pub fn geo(_key: ApiKey, lat: f64, lng: f64, alt: Option<f64>, opts: QrOptions) -> Result<Image, ApiError> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        return Err(ApiError::invalid("lat must be -90..90 and lng -180..180"));
+    }
+    let payload = match alt {
+        Some(alt) if alt.is_finite() => format!("geo:{},{},{}", lat, lng, alt),
+        Some(_) => return Err(ApiError::invalid("alt must be a number")),
+        None => format!("geo:{},{}", lat, lng),
+    };
+    render_qr(&payload, opts)
+}
+
+/// `/qr/sms?phone=+15551234567&message=hi` -> `SMSTO:+15551234567:hi`
+#[get("/qr/sms?<phone>&<message>&<opts..>")]
+pub fn sms(_key: ApiKey, phone: String, message: Option<String>, opts: QrOptions) -> Result<Image, ApiError> {
+    let phone = normalize_phone(&phone)?;
+    render_qr(&format!("SMSTO:{}:{}", phone, message.unwrap_or_default()), opts)
+}
+
+/// Drops spacing and punctuation people write in numbers; what's left must be digits
+/// with an optional leading `+`.
+fn normalize_phone(phone: &str) -> Result<String, ApiError> {
+    let phone: String = phone.chars().filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')')).collect();
+    let digits = phone.strip_prefix('+').unwrap_or(&phone);
+    if !(3..=20).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ApiError::invalid("phone must be 3-20 digits, optionally starting with +"));
+    }
+    Ok(phone)
+}
+
+/// `/qr/email?to=a@example.com&subject=Hi` -> `mailto:a@example.com?subject=Hi`
+#[get("/qr/email?<to>&<cc>&<subject>&<body>&<opts..>")]
+pub fn email(
+    _key: ApiKey,
+    to: String,
+    cc: Option<String>,
+    subject: Option<String>,
+    body: Option<String>,
+    opts: QrOptions,
+) -> Result<Image, ApiError> {
+    let to = email_list(&to)?;
+    let mut query = Vec::new();
+    if let Some(cc) = cc.filter(|c| !c.trim().is_empty()) {
+        query.push(format!("cc={}", email_list(&cc)?));
+    }
+    for (key, value) in [("subject", subject), ("body", body)] {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            query.push(format!("{}={}", key, percent_encode(value.as_bytes())));
+        }
+    }
+    let mut payload = format!("mailto:{}", to);
+    if !query.is_empty() {
+        payload.push('?');
+        payload.push_str(&query.join("&"));
+    }
+    render_qr(&payload, opts)
+}
+
+/// Comma-separated addresses, each a plain `local@domain` (no display names).
+fn email_list(list: &str) -> Result<String, ApiError> {
+    let addresses: Vec<&str> = list.split(',').map(str::trim).collect();
+    for address in &addresses {
+        let valid = address.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && domain.contains('.') && !domain.contains('@')
+        }) && !address.chars().any(|c| c.is_whitespace() || "?&<>\"".contains(c));
+        if !valid {
+            return Err(ApiError::invalid(format!("'{}' is not an email address", address)));
+        }
+    }
+    Ok(addresses.join(","))
+}
+
+/// `/qr/event?summary=Standup&start=2026-10-17T09:00:00%2B04:00&end=...` -> an iCalendar VEVENT.
+/// Times with an offset are stored in UTC; without one they stay floating (scanner's
+/// local time); a bare date makes an all-day event.
+#[get("/qr/event?<summary>&<start>&<end>&<location>&<description>&<opts..>")]
+pub fn event(
+    _key: ApiKey,
+    summary: String,
+    start: String,
+    end: Option<String>,
+    location: Option<String>,
+    description: Option<String>,
+    opts: QrOptions,
+) -> Result<Image, ApiError> {
+    if summary.trim().is_empty() {
+        return Err(ApiError::bad_request("provide ?summary=..."));
+    }
+    let start = EventTime::parse(&start)?;
+    let end = match end.filter(|e| !e.is_empty()) {
+        Some(end) => Some(EventTime::parse(&end)?),
+        // DTEND is exclusive, so a one-day event ends the next day.
+        None => match start {
+            EventTime::Date(d) => match d.checked_add_days(Days::new(1)) {
+                Some(next) => Some(EventTime::Date(next)),
+                None => return Err(ApiError::invalid("start is the last representable date")),
+            },
+            _ => None,
+        },
+    };
+    if let Some(end) = &end {
+        if !start.comparable(end) {
+            return Err(ApiError::invalid("start and end must both be dates, both have offsets or both be local"));
+        }
+        if end.sort_key() < start.sort_key() {
+            return Err(ApiError::invalid("end is before start"));
+        }
+    }
+
+    let mut lines = vec!["BEGIN:VEVENT".to_string(), format!("SUMMARY:{}", vcard_escape(summary.trim()))];
+    lines.push(format!("DTSTART{}", start.ical()));
+    if let Some(end) = end {
+        lines.push(format!("DTEND{}", end.ical()));
+    }
+    for (key, value) in [("LOCATION", location), ("DESCRIPTION", description)] {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            lines.push(format!("{}:{}", key, vcard_escape(value.trim())));
+        }
+    }
+    lines.push("END:VEVENT".to_string());
+    render_qr(&lines.join("\r\n"), opts)
+}
+
+enum EventTime {
+    Date(NaiveDate),
+    Utc(DateTime<Utc>),
+    Floating(NaiveDateTime),
+}
+
+impl EventTime {
+    fn parse(s: &str) -> Result<EventTime, ApiError> {
+        let s = s.trim();
+        if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+            return Ok(EventTime::Utc(t.with_timezone(&Utc)));
+        }
+        for fmt in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+            if let Ok(t) = NaiveDateTime::parse_from_str(s, fmt) {
+                return Ok(EventTime::Floating(t));
+            }
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").map(EventTime::Date).map_err(|_| {
+            ApiError::invalid(format!(
+                "'{}' is not a time (use 2026-10-17, 2026-10-17T09:00 or RFC 3339 with an offset)",
+                s
+            ))
+        })
+    }
+
+    fn comparable(&self, other: &EventTime) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    fn sort_key(&self) -> NaiveDateTime {
+        match self {
+            EventTime::Date(d) => d.and_hms_opt(0, 0, 0).unwrap_or_default(),
+            EventTime::Utc(t) => t.naive_utc(),
+            EventTime::Floating(t) => *t,
+        }
+    }
+
+    /// Property parameters and value, e.g. `;VALUE=DATE:20261017` or `:20261017T050000Z`.
+    fn ical(&self) -> String {
+        match self {
+            EventTime::Date(d) => d.format(";VALUE=DATE:%Y%m%d").to_string(),
+            EventTime::Utc(t) => t.format(":%Y%m%dT%H%M%SZ").to_string(),
+            EventTime::Floating(t) => t.format(":%Y%m%dT%H%M%S").to_string(),
+        }
+    }
+}
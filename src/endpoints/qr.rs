@@ -1,132 +1,465 @@
-use qrcode::render::svg;
-use qrcode::QrCode;
-use rocket::http::ContentType;
-
-use barcoders::generators::svg::SVG as BarcodeSVG;
-use barcoders::sym::code39::Code39;
-use barcoders::sym::code128::Code128;
-use barcoders::sym::ean13::EAN13;
+use qrcode::{EcLevel, QrCode, Version};
+use rocket::http::{ContentType, Header};
+use rocket::serde::json::serde_json::json;
 
 use datamatrix::{DataMatrix, SymbolList};
 
+use crate::auth::ApiKey;
+use crate::aztec;
+use crate::barcode::{Symbology, SYMBOLOGIES};
+use crate::endpoints::convert::parse_hex_color;
+use crate::gs1;
+use crate::pdf417;
+use crate::raster::{Layout, Modules, GLYPH_ADVANCE};
+use crate::response::ApiError;
+
 // SECURITY: hard caps on input so code generation can't be used to burn CPU/memory.
-const MAX_QR_TEXT: usize = 1_000;
-const MAX_BARCODE_TEXT: usize = 200;
-const MAX_DM_TEXT: usize = 500;
+pub const MAX_QR_TEXT: usize = 1_000;
+pub const MAX_BARCODE_TEXT: usize = 200;
+pub const MAX_DM_TEXT: usize = 500;
+pub const MAX_AZTEC_TEXT: usize = 1_000;
+pub const MAX_PDF417_TEXT: usize = 1_000;
+// ...and on output: raster images are built in memory.
+pub const MAX_SCALE: usize = 32;
+pub const MAX_IMAGE_PX: usize = 4096;
+pub const MAX_QR_MARGIN: usize = 20;
+pub const MAX_BAR_HEIGHT: usize = 1_000;
 
-/// `/qr?text=hello` -> an SVG QR code (Content-Type: image/svg+xml).
-#[get("/qr?<text>")]
-pub fn qr(text: String) -> (ContentType, String) {
-    if text.is_empty() {
-        return (ContentType::Plain, "Error: provide ?text=...".to_string());
-    }
-    if text.len() > MAX_QR_TEXT {
-        return (
-            ContentType::Plain,
-            format!("Error: text too large (max {} chars)", MAX_QR_TEXT),
-        );
-    }
-    match QrCode::new(text.as_bytes()) {
-        Ok(code) => {
-            let image = code
-                .render::<svg::Color>()
-                .min_dimensions(200, 200)
-                .max_dimensions(600, 600)
-                .build();
-            (ContentType::SVG, image)
+const BAR_HEIGHT: usize = 120;
+/// Modules of quiet zone either side of a barcode (ITF-14 and GS1-128 ask for 10).
+pub const BAR_MARGIN: usize = 10;
+const BLACK: [u8; 3] = [0, 0, 0];
+const WHITE: [u8; 3] = [255, 255, 255];
+
+pub type Image = (ContentType, Vec<u8>);
+
+#[derive(PartialEq)]
+enum ImageFormat {
+    Svg,
+    Png,
+    Txt,
+}
+
+/// `?format=svg|png|txt` plus either `?scale=` (pixels per module) or `?size=` (exact
+/// width in pixels; modules stay whole pixels and the leftover widens the quiet zone).
+struct ImageOptions {
+    format: ImageFormat,
+    scale: Option<usize>,
+    size: Option<usize>,
+    fg: [u8; 3],
+    bg: [u8; 3],
+}
+
+impl ImageOptions {
+    fn parse(format: Option<&str>, size: Option<usize>, scale: Option<usize>) -> Result<ImageOptions, ApiError> {
+        let format = match format.map(str::to_lowercase).as_deref() {
+            None | Some("svg") => ImageFormat::Svg,
+            Some("png") => ImageFormat::Png,
+            Some("txt") | Some("text") => ImageFormat::Txt,
+            Some(_) => return Err(ApiError::unsupported("unknown format (svg, png, txt)")),
+        };
+        if size.is_some() && scale.is_some() {
+            return Err(ApiError::bad_request("use either ?size= or ?scale=, not both"));
+        }
+        if scale.is_some_and(|s| !(1..=MAX_SCALE).contains(&s)) {
+            return Err(ApiError::invalid(format!("scale must be 1-{}", MAX_SCALE)));
+        }
+        if size.is_some_and(|s| !(16..=MAX_IMAGE_PX).contains(&s)) {
+            return Err(ApiError::invalid(format!("size must be 16-{} px", MAX_IMAGE_PX)));
         }
-        Err(_) => (
-            ContentType::Plain,
-            "Error: input exceeds QR code capacity".to_string(),
-        ),
+        Ok(ImageOptions { format, scale, size, fg: BLACK, bg: WHITE })
     }
+
+    /// `?fg=` / `?bg=` hex colors. Text output ignores them.
+    fn colors(mut self, fg: Option<&str>, bg: Option<&str>) -> Result<ImageOptions, ApiError> {
+        if let Some(fg) = fg {
+            self.fg = parse_hex_color(fg).ok_or_else(|| ApiError::invalid("fg must be a 6-digit hex color like 1a2b3c"))?;
+        }
+        if let Some(bg) = bg {
+            self.bg = parse_hex_color(bg).ok_or_else(|| ApiError::invalid("bg must be a 6-digit hex color like ffffff"))?;
+        }
+        if self.fg == self.bg {
+            return Err(ApiError::invalid("fg and bg must differ"));
+        }
+        Ok(self)
+    }
+
+    /// `bar_height` fixes the module height for 1D codes; modules are square otherwise.
+    /// A caption is drawn in a band under the code.
+    fn render(
+        &self,
+        modules: &Modules,
+        default_px: usize,
+        bar_height: Option<usize>,
+        caption: Option<&str>,
+    ) -> Result<Image, ApiError> {
+        let module_w = match (self.scale, self.size) {
+            (Some(scale), _) => scale,
+            (_, Some(size)) => (size / modules.width().max(1)).max(1),
+            _ => default_px,
+        };
+        let mut layout = Layout::fit(modules, module_w, bar_height.unwrap_or(module_w));
+        if let Some(size) = self.size {
+            layout.width = layout.width.max(size);
+            if bar_height.is_none() {
+                layout.height = layout.height.max(size);
+            }
+        } else if let Some(caption) = caption {
+            // Leave room for the caption at the smallest font rather than clip it.
+            layout.width = layout.width.max(caption.chars().count() * GLYPH_ADVANCE);
+        }
+        if let Some(caption) = caption {
+            layout = layout.with_caption(caption.chars().count());
+        }
+        match self.format {
+            ImageFormat::Svg => Ok((ContentType::SVG, modules.to_svg(layout, self.fg, self.bg, caption).into_bytes())),
+            ImageFormat::Txt => Ok((ContentType::Plain, modules.to_text(caption).into_bytes())),
+            ImageFormat::Png => {
+                if layout.width > MAX_IMAGE_PX || layout.height > MAX_IMAGE_PX {
+                    return Err(ApiError::invalid(format!(
+                        "image would be {}x{} px (max {}); lower ?scale= or ?size=",
+                        layout.width, layout.height, MAX_IMAGE_PX
+                    )));
+                }
+                Ok((ContentType::PNG, modules.to_png(layout, self.fg, self.bg, caption)))
+            }
+        }
+    }
+}
+
+/// Rendering options shared by `/qr` and the `/qr/*` payload builders. `?ecc=` defaults
+/// to M, `?version=` to the smallest that fits and `?margin=` to the standard 4-module
+/// quiet zone.
+#[derive(FromForm, Default)]
+pub struct QrOptions {
+    format: Option<String>,
+    size: Option<usize>,
+    scale: Option<usize>,
+    ecc: Option<String>,
+    version: Option<i16>,
+    margin: Option<usize>,
+    fg: Option<String>,
+    bg: Option<String>,
 }
 
-/// `/barcode?text=ABC123&symbology=code128` -> SVG 1D barcode.
-/// symbology: code128 (default), code39, ean13.
-#[get("/barcode?<text>&<symbology>")]
-pub fn barcode(text: String, symbology: Option<String>) -> (ContentType, String) {
+/// `/qr?text=hello` -> an SVG QR code (Content-Type: image/svg+xml); `?format=png|txt` for
+/// raster or terminal output.
+#[get("/qr?<text>&<opts..>")]
+pub fn qr(_key: ApiKey, text: String, opts: QrOptions) -> Result<Image, ApiError> {
     if text.is_empty() {
-        return (ContentType::Plain, "Error: provide ?text=...".to_string());
+        return Err(ApiError::bad_request("provide ?text=..."));
     }
-    if text.len() > MAX_BARCODE_TEXT {
-        return (
-            ContentType::Plain,
-            format!("Error: text too large (max {} chars)", MAX_BARCODE_TEXT),
-        );
-    }
-    let sym = symbology.unwrap_or_else(|| "code128".to_string()).to_lowercase();
-
-    let encoded: Result<Vec<u8>, String> = match sym.as_str() {
-        "code39" => Code39::new(&text)
-            .map(|b| b.encode())
-            .map_err(|_| "invalid data for Code39 (use A-Z, 0-9, space, -.$/+%)".to_string()),
-        "ean13" => EAN13::new(&text)
-            .map(|b| b.encode())
-            .map_err(|_| "invalid data for EAN-13 (use 12-13 digits)".to_string()),
-        "code128" => {
-            // Code128 in barcoders needs a leading code-set char; Ɓ = code set B (ASCII).
-            Code128::new(format!("\u{0181}{}", text))
-                .map(|b| b.encode())
-                .map_err(|_| "invalid data for Code128".to_string())
-        }
-        _ => return (ContentType::Plain, "Error: unknown symbology (code128, code39, ean13)".to_string()),
+    render_qr(&text, opts)
+}
+
+/// Encodes `payload` per `opts`. `?format=json` returns the payload and symbol details
+/// instead of an image, so callers can see exactly what a builder encoded.
+pub fn render_qr(payload: &str, opts: QrOptions) -> Result<Image, ApiError> {
+    let json = opts.format.as_deref().is_some_and(|f| f.eq_ignore_ascii_case("json"));
+    let image = if json {
+        None
+    } else {
+        let image = ImageOptions::parse(opts.format.as_deref(), opts.size, opts.scale)?;
+        Some(image.colors(opts.fg.as_deref(), opts.bg.as_deref())?)
+    };
+    if payload.len() > MAX_QR_TEXT {
+        return Err(ApiError::too_large(format!("text too large (max {} chars)", MAX_QR_TEXT)));
+    }
+    let ecc = match opts.ecc.as_deref().map(str::to_uppercase).as_deref() {
+        None | Some("M") => EcLevel::M,
+        Some("L") => EcLevel::L,
+        Some("Q") => EcLevel::Q,
+        Some("H") => EcLevel::H,
+        Some(_) => return Err(ApiError::unsupported("unknown ecc level (L, M, Q, H)")),
     };
+    let margin = opts.margin.unwrap_or(4);
+    if margin > MAX_QR_MARGIN {
+        return Err(ApiError::invalid(format!("margin must be 0-{} modules", MAX_QR_MARGIN)));
+    }
+    let code = qr_code(payload, ecc, opts.version)?;
+    let width = code.width();
 
-    match encoded {
-        Ok(enc) => match BarcodeSVG::new(120).generate(&enc[..]) {
-            Ok(s) => {
-                // barcoders omits the SVG xmlns, which breaks <img>/standalone rendering.
-                let s = if s.contains("xmlns") {
-                    s
-                } else {
-                    s.replacen("<svg", "<svg xmlns=\"http://www.w3.org/2000/svg\"", 1)
-                };
-                (ContentType::SVG, s)
-            }
-            Err(_) => (ContentType::Plain, "Error: failed to render barcode".to_string()),
-        },
-        Err(msg) => (ContentType::Plain, format!("Error: {}", msg)),
+    let Some(image) = image else {
+        let version = match code.version() {
+            Version::Normal(v) | Version::Micro(v) => v,
+        };
+        let body = json!({
+            "payload": payload,
+            "version": version,
+            "ecc": format!("{:?}", ecc),
+            "modules": width,
+        });
+        return Ok((ContentType::JSON, body.to_string().into_bytes()));
+    };
+    image.render(&qr_modules(&code).with_quiet_zone(margin, margin), 8, None, None)
+}
+
+/// `version` picks the symbol size; otherwise the smallest that fits.
+pub fn qr_code(payload: &str, ecc: EcLevel, version: Option<i16>) -> Result<QrCode, ApiError> {
+    match version {
+        None => QrCode::with_error_correction_level(payload.as_bytes(), ecc)
+            .map_err(|_| ApiError::too_large("input exceeds QR code capacity")),
+        Some(v @ 1..=40) => QrCode::with_version(payload.as_bytes(), Version::Normal(v), ecc)
+            .map_err(|_| ApiError::invalid(format!("text doesn't fit in version {} at this ecc level", v))),
+        Some(_) => Err(ApiError::invalid("version must be 1-40")),
     }
 }
 
-/// `/datamatrix?text=hello` -> SVG Data Matrix (ECC 200).
-#[get("/datamatrix?<text>")]
-pub fn data_matrix(text: String) -> (ContentType, String) {
+pub fn qr_modules(code: &QrCode) -> Modules {
+    let width = code.width();
+    let colors = code.to_colors();
+    Modules::from_fn(width, width, |x, y| colors[y * width + x] == qrcode::Color::Dark)
+}
+
+/// Options for `/barcode`: `?scale=` is the module (narrowest bar) width in pixels,
+/// `?height=` the bar height, `?margin=` the quiet zone in modules each side and
+/// `?show_text=false` drops the human-readable line.
+#[derive(FromForm, Default)]
+pub struct BarcodeOptions {
+    format: Option<String>,
+    size: Option<usize>,
+    scale: Option<usize>,
+    height: Option<usize>,
+    margin: Option<usize>,
+    show_text: Option<bool>,
+    fg: Option<String>,
+    bg: Option<String>,
+}
+
+/// `/barcode?text=ABC123&symbology=code128` -> SVG 1D barcode with its human-readable
+/// text. Code128 switches between sets A/B/C as the data needs; `gs1-128` takes
+/// `(AI)value` pairs.
+#[get("/barcode?<text>&<symbology>&<opts..>")]
+pub fn barcode(_key: ApiKey, text: String, symbology: Option<String>, opts: BarcodeOptions) -> Result<Image, ApiError> {
+    let image = ImageOptions::parse(opts.format.as_deref(), opts.size, opts.scale)?
+        .colors(opts.fg.as_deref(), opts.bg.as_deref())?;
     if text.is_empty() {
-        return (ContentType::Plain, "Error: provide ?text=...".to_string());
+        return Err(ApiError::bad_request("provide ?text=..."));
+    }
+    if text.len() > MAX_BARCODE_TEXT {
+        return Err(ApiError::too_large(format!("text too large (max {} chars)", MAX_BARCODE_TEXT)));
+    }
+    let symbology: Symbology = symbology
+        .as_deref()
+        .unwrap_or("code128")
+        .parse()
+        .map_err(|_| ApiError::unsupported(format!("unknown symbology ({})", SYMBOLOGIES)))?;
+    let height = opts.height.unwrap_or(BAR_HEIGHT);
+    if !(10..=MAX_BAR_HEIGHT).contains(&height) {
+        return Err(ApiError::invalid(format!("height must be 10-{} px", MAX_BAR_HEIGHT)));
+    }
+    let margin = opts.margin.unwrap_or(BAR_MARGIN);
+    if margin > MAX_QR_MARGIN {
+        return Err(ApiError::invalid(format!("margin must be 0-{} modules", MAX_QR_MARGIN)));
+    }
+
+    let encoded = symbology.encode(&text).map_err(ApiError::invalid)?;
+    // One row of bars; text output gets a few so the bars read as bars.
+    let rows = if image.format == ImageFormat::Txt { 6 } else { 1 };
+    let bars = encoded.bars;
+    let modules = Modules::from_fn(bars.len(), rows, |x, _| bars[x] == 1).with_quiet_zone(margin, 0);
+    let caption = Some(encoded.text.as_str()).filter(|_| opts.show_text.unwrap_or(true));
+    image.render(&modules, 1, Some(height), caption)
+}
+
+/// Options for `/datamatrix`: `?shape=square|rectangle` limits the symbol sizes tried,
+/// `?symbol=` forces one (`16` or `16x48`, rows by columns, DMRE sizes included), `?gs1=true`
+/// takes `(AI)value` pairs and `?margin=` is the quiet zone in modules.
+#[derive(FromForm, Default)]
+pub struct DataMatrixOptions {
+    format: Option<String>,
+    size: Option<usize>,
+    scale: Option<usize>,
+    shape: Option<String>,
+    symbol: Option<String>,
+    gs1: Option<bool>,
+    margin: Option<usize>,
+    fg: Option<String>,
+    bg: Option<String>,
+}
+
+/// A 2D code image with its symbol size in modules, rows by columns, in `X-Symbol-Size`.
+#[derive(Responder)]
+pub struct SymbolImage {
+    image: Image,
+    symbol_size: Header<'static>,
+}
+
+/// `/datamatrix?text=hello` -> SVG Data Matrix (ECC 200); the smallest symbol that fits
+/// unless `?shape=` or `?symbol=` says otherwise.
+#[get("/datamatrix?<text>&<opts..>")]
+pub fn data_matrix(_key: ApiKey, text: String, opts: DataMatrixOptions) -> Result<SymbolImage, ApiError> {
+    let image = ImageOptions::parse(opts.format.as_deref(), opts.size, opts.scale)?
+        .colors(opts.fg.as_deref(), opts.bg.as_deref())?;
+    if text.is_empty() {
+        return Err(ApiError::bad_request("provide ?text=..."));
     }
     if text.len() > MAX_DM_TEXT {
-        return (
-            ContentType::Plain,
-            format!("Error: text too large (max {} chars)", MAX_DM_TEXT),
-        );
+        return Err(ApiError::too_large(format!("text too large (max {} chars)", MAX_DM_TEXT)));
     }
-    match DataMatrix::encode(text.as_bytes(), SymbolList::default()) {
-        Ok(code) => (ContentType::SVG, datamatrix_to_svg(&code)),
-        Err(_) => (ContentType::Plain, "Error: could not encode Data Matrix".to_string()),
+    let margin = opts.margin.unwrap_or(1);
+    if margin > MAX_QR_MARGIN {
+        return Err(ApiError::invalid(format!("margin must be 0-{} modules", MAX_QR_MARGIN)));
     }
+    let symbols = match opts.shape.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("auto") => SymbolList::default(),
+        Some("square") => SymbolList::default().enforce_square(),
+        Some("rectangle") | Some("rectangular") => SymbolList::default().enforce_rectangular(),
+        Some(_) => return Err(ApiError::unsupported("unknown shape (auto, square, rectangle)")),
+    };
+    let symbols = match opts.symbol.as_deref() {
+        None => symbols,
+        Some(symbol) => {
+            if opts.shape.is_some() {
+                return Err(ApiError::bad_request("use either ?shape= or ?symbol=, not both"));
+            }
+            let (rows, cols) = parse_symbol_size(symbol)
+                .ok_or_else(|| ApiError::invalid("symbol must be a size like 16 or 16x48 (rows x columns)"))?;
+            let list = SymbolList::all().enforce_height_in(rows..=rows).enforce_width_in(cols..=cols);
+            if list.is_empty() {
+                return Err(ApiError::unsupported(format!("no Data Matrix symbol is {}x{}", rows, cols)));
+            }
+            list
+        }
+    };
+    let modules = if opts.gs1.unwrap_or(false) {
+        let elements = gs1::parse(&text).map_err(ApiError::invalid)?;
+        data_matrix_modules(gs1::encode(&elements, '\x1d').as_bytes(), true, symbols)
+    } else {
+        data_matrix_modules(text.as_bytes(), false, symbols)
+    };
+    let modules = modules.ok_or_else(|| match opts.symbol {
+        Some(symbol) => ApiError::invalid(format!("text doesn't fit in a {} symbol", symbol)),
+        None => ApiError::too_large("input exceeds Data Matrix capacity"),
+    })?;
+    let symbol_size = format!("{}x{}", modules.height(), modules.width());
+    Ok(SymbolImage {
+        image: image.render(&modules.with_quiet_zone(margin, margin), 8, None, None)?,
+        symbol_size: Header::new("X-Symbol-Size", symbol_size),
+    })
 }
 
-fn datamatrix_to_svg(code: &datamatrix::DataMatrix) -> String {
+/// The smallest symbol in `symbols` that holds `data`, if any; `gs1` starts it with FNC1.
+pub fn data_matrix_modules(data: &[u8], gs1: bool, symbols: SymbolList) -> Option<Modules> {
+    let code = if gs1 { DataMatrix::encode_gs1(data, symbols) } else { DataMatrix::encode(data, symbols) }.ok()?;
     let bitmap = code.bitmap();
-    let w = bitmap.width();
-    let h = bitmap.height();
-    let scale = 8usize;
-    let quiet = 1usize; // 1-module quiet zone
-    let total_w = (w + 2 * quiet) * scale;
-    let total_h = (h + 2 * quiet) * scale;
-    let mut rects = String::new();
+    let mut dark = vec![false; bitmap.width() * bitmap.height()];
     for (x, y) in bitmap.pixels() {
-        let px = (x + quiet) * scale;
-        let py = (y + quiet) * scale;
-        rects.push_str(&format!(
-            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
-            px, py, scale, scale
-        ));
-    }
-    format!(
-        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\"><rect width=\"100%\" height=\"100%\" fill=\"#fff\"/><g fill=\"#000\">{}</g></svg>",
-        total_w, total_h, rects
-    )
+        dark[y * bitmap.width() + x] = true;
+    }
+    Some(Modules::from_fn(bitmap.width(), bitmap.height(), |x, y| dark[y * bitmap.width() + x]))
+}
+
+/// `16` or `12x26`, rows by columns.
+fn parse_symbol_size(symbol: &str) -> Option<(usize, usize)> {
+    match symbol.split_once(['x', 'X']) {
+        Some((rows, cols)) => Some((rows.trim().parse().ok()?, cols.trim().parse().ok()?)),
+        None => symbol.trim().parse().ok().map(|n| (n, n)),
+    }
+}
+
+/// Options for `/aztec`: `?ecc=` is the check-word share in percent, `?layers=` forces the
+/// symbol size and `?compact=` the compact (1-4 layers) or full-range form.
+#[derive(FromForm, Default)]
+pub struct AztecOptions {
+    format: Option<String>,
+    size: Option<usize>,
+    scale: Option<usize>,
+    ecc: Option<usize>,
+    layers: Option<usize>,
+    compact: Option<bool>,
+    margin: Option<usize>,
+    fg: Option<String>,
+    bg: Option<String>,
+}
+
+/// `/aztec?text=hello` -> SVG Aztec Code, the smallest symbol that fits unless `?layers=`
+/// says otherwise.
+#[get("/aztec?<text>&<opts..>")]
+pub fn aztec_code(_key: ApiKey, text: String, opts: AztecOptions) -> Result<SymbolImage, ApiError> {
+    let image = ImageOptions::parse(opts.format.as_deref(), opts.size, opts.scale)?
+        .colors(opts.fg.as_deref(), opts.bg.as_deref())?;
+    if text.is_empty() {
+        return Err(ApiError::bad_request("provide ?text=..."));
+    }
+    if text.len() > MAX_AZTEC_TEXT {
+        return Err(ApiError::too_large(format!("text too large (max {} chars)", MAX_AZTEC_TEXT)));
+    }
+    let ecc = opts.ecc.unwrap_or(aztec::DEFAULT_ECC_PERCENT);
+    if !(aztec::MIN_ECC_PERCENT..=aztec::MAX_ECC_PERCENT).contains(&ecc) {
+        return Err(ApiError::invalid(format!(
+            "ecc must be {}-{} (percent)",
+            aztec::MIN_ECC_PERCENT,
+            aztec::MAX_ECC_PERCENT
+        )));
+    }
+    let max_layers = if opts.compact == Some(true) { aztec::MAX_COMPACT_LAYERS } else { aztec::MAX_LAYERS };
+    if opts.layers.is_some_and(|n| !(1..=max_layers).contains(&n)) {
+        return Err(ApiError::invalid(format!("layers must be 1-{}", max_layers)));
+    }
+    let margin = opts.margin.unwrap_or(2);
+    if margin > MAX_QR_MARGIN {
+        return Err(ApiError::invalid(format!("margin must be 0-{} modules", MAX_QR_MARGIN)));
+    }
+    let modules = aztec_modules(&text, ecc, opts.layers, opts.compact)?;
+    let symbol_size = format!("{0}x{0}", modules.width());
+    Ok(SymbolImage {
+        image: image.render(&modules.with_quiet_zone(margin, margin), 8, None, None)?,
+        symbol_size: Header::new("X-Symbol-Size", symbol_size),
+    })
+}
+
+pub fn aztec_modules(text: &str, ecc: usize, layers: Option<usize>, compact: Option<bool>) -> Result<Modules, ApiError> {
+    let code = aztec::encode(text.as_bytes(), ecc, layers, compact).map_err(|e| match e {
+        aztec::Error::TooLarge => ApiError::too_large("input exceeds Aztec capacity"),
+        aztec::Error::Layers(msg) => ApiError::invalid(msg),
+    })?;
+    Ok(Modules::from_fn(code.size, code.size, |x, y| code.is_dark(x, y)))
+}
+
+/// Options for `/pdf417`: `?ecc=` is the error correction level (0-8) and `?columns=` the
+/// number of data columns.
+#[derive(FromForm, Default)]
+pub struct Pdf417Options {
+    format: Option<String>,
+    size: Option<usize>,
+    scale: Option<usize>,
+    ecc: Option<usize>,
+    columns: Option<usize>,
+    margin: Option<usize>,
+    fg: Option<String>,
+    bg: Option<String>,
+}
+
+/// `/pdf417?text=hello` -> SVG PDF417, about three times as wide as it is tall unless
+/// `?columns=` says otherwise. `X-Symbol-Size` has the codeword rows by data columns.
+#[get("/pdf417?<text>&<opts..>")]
+pub fn pdf417_code(_key: ApiKey, text: String, opts: Pdf417Options) -> Result<SymbolImage, ApiError> {
+    let image = ImageOptions::parse(opts.format.as_deref(), opts.size, opts.scale)?
+        .colors(opts.fg.as_deref(), opts.bg.as_deref())?;
+    if text.is_empty() {
+        return Err(ApiError::bad_request("provide ?text=..."));
+    }
+    if text.len() > MAX_PDF417_TEXT {
+        return Err(ApiError::too_large(format!("text too large (max {} chars)", MAX_PDF417_TEXT)));
+    }
+    if opts.ecc.is_some_and(|level| level > pdf417::MAX_ECC_LEVEL) {
+        return Err(ApiError::invalid(format!("ecc must be 0-{}", pdf417::MAX_ECC_LEVEL)));
+    }
+    if opts.columns.is_some_and(|n| !(1..=pdf417::MAX_COLUMNS).contains(&n)) {
+        return Err(ApiError::invalid(format!("columns must be 1-{}", pdf417::MAX_COLUMNS)));
+    }
+    let margin = opts.margin.unwrap_or(2);
+    if margin > MAX_QR_MARGIN {
+        return Err(ApiError::invalid(format!("margin must be 0-{} modules", MAX_QR_MARGIN)));
+    }
+    let code = pdf417::encode(text.as_bytes(), opts.ecc, opts.columns).map_err(|e| match e {
+        pdf417::Error::TooLarge => ApiError::too_large("input exceeds PDF417 capacity"),
+        pdf417::Error::Columns(msg) => ApiError::invalid(msg),
+    })?;
+    let modules = Modules::from_fn(code.width, code.rows * pdf417::ROW_HEIGHT, |x, y| code.is_dark(x, y / pdf417::ROW_HEIGHT));
+    Ok(SymbolImage {
+        image: image.render(&modules.with_quiet_zone(margin, margin), 2, None, None)?,
+        symbol_size: Header::new("X-Symbol-Size", format!("{}x{}", code.rows, code.columns)),
+    })
 }
@@ -0,0 +1,54 @@
+use rocket::serde::json::serde_json::json;
+
+use crate::auth::ApiKey;
+use crate::barcode::{Symbology, SYMBOLOGIES};
+use crate::extractors::ImageUpload;
+use crate::response::{ApiError, Reply};
+use crate::reader::{self, Decoded};
+
+// Reading codes back out of uploaded images; the decoding itself lives in `crate::reader`.
+
+/// `POST /qr/decode` with a PNG or JPEG, as the raw body or a multipart `image` field
+/// -> the QR code's text, version and EC level, and where it sits in the image.
+#[post("/qr/decode", data = "<image>")]
+pub fn qr_decode(_key: ApiKey, image: Result<ImageUpload, ApiError>) -> Result<Reply, ApiError> {
+    let gray = reader::decode_image(&image?.0).map_err(ApiError::invalid)?;
+    let found = reader::qr::decode(&reader::binarize(&gray)).map_err(ApiError::invalid)?;
+    Ok(decoded_reply(found))
+}
+
+/// `POST /barcode/decode` -> the first 1D barcode found in a PNG or JPEG; `?symbology=`
+/// restricts the search to one symbology.
+#[post("/barcode/decode?<symbology>", data = "<image>")]
+pub fn barcode_decode(_key: ApiKey, symbology: Option<String>, image: Result<ImageUpload, ApiError>) -> Result<Reply, ApiError> {
+    let only = symbology
+        .map(|s| s.parse::<Symbology>().map_err(|_| ApiError::unsupported(format!("unknown symbology ({})", SYMBOLOGIES))))
+        .transpose()?;
+    let gray = reader::decode_image(&image?.0).map_err(ApiError::invalid)?;
+    let found = reader::linear::decode(&reader::binarize(&gray), only).map_err(ApiError::invalid)?;
+    Ok(decoded_reply(found))
+}
+
+fn decoded_reply(found: Decoded) -> Reply {
+    let round = |v: f64| (v * 10.0).round() / 10.0;
+    let xs = found.corners.map(|c| c.0);
+    let ys = found.corners.map(|c| c.1);
+    let (x0, x1) = (xs.iter().cloned().fold(f64::MAX, f64::min), xs.iter().cloned().fold(f64::MIN, f64::max));
+    let (y0, y1) = (ys.iter().cloned().fold(f64::MAX, f64::min), ys.iter().cloned().fold(f64::MIN, f64::max));
+    let mut body = json!({
+        "text": found.text,
+        "symbology": found.symbology,
+        "bounds": {
+            "x": round(x0),
+            "y": round(y0),
+            "width": round(x1 - x0),
+            "height": round(y1 - y0),
+        },
+        "corners": found.corners.map(|(x, y)| [round(x), round(y)]),
+    });
+    if let Some((version, ecc)) = found.qr {
+        body["version"] = json!(version);
+        body["ecc"] = json!(ecc.to_string());
+    }
+    Reply::json(body)
+}
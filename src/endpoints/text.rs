@@ -1,15 +1,18 @@
-use rocket::serde::json::{serde_json, Json, Value};
+use rocket::serde::json::serde_json;
+
+use crate::auth::ApiKey;
+use crate::response::{ApiError, Reply};
 
 // SECURITY: cap text inputs so no single request can exhaust CPU/memory.
-const MAX_TEXT: usize = 10_000;
+pub const MAX_TEXT: usize = 10_000;
 
-fn too_large_str() -> String {
-    "Error: input too large (max 10000 chars)".to_string()
+fn too_large_str() -> Reply {
+    ApiError::too_large("input too large (max 10000 chars)").into()
 }
 
 /// `/text/slugify?text=Hello World!` -> `hello-world`
 #[get("/text/slugify?<text>")]
-pub fn slugify(text: String) -> String {
+pub fn slugify(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_TEXT {
         return too_large_str();
     }
@@ -27,25 +30,25 @@ pub fn slugify(text: String) -> String {
     while slug.ends_with('-') {
         slug.pop();
     }
-    slug
+    Reply::text(slug)
 }
 
 /// `/text/reverse?text=abc` -> `cba`
 #[get("/text/reverse?<text>")]
-pub fn reverse(text: String) -> String {
+pub fn reverse(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_TEXT {
         return too_large_str();
     }
-    text.chars().rev().collect()
+    Reply::text(text.chars().rev().collect::<String>())
 }
 
 /// `/text/count?text=...` -> counts of chars/bytes/words/lines
 #[get("/text/count?<text>")]
-pub fn count(text: String) -> Json<Value> {
+pub fn count(_key: ApiKey, text: String) -> Reply {
     if text.len() > MAX_TEXT {
-        return Json(serde_json::json!({"error": "input too large (max 10000 chars)"}));
+        return ApiError::too_large("input too large (max 10000 chars)").into();
     }
-    Json(serde_json::json!({
+    Reply::json(serde_json::json!({
         "characters": text.chars().count(),
         "bytes": text.len(),
         "words": text.split_whitespace().count(),
@@ -55,11 +58,11 @@ pub fn count(text: String) -> Json<Value> {
 
 /// `/text/case/<mode>?text=...` where mode = upper|lower|title|snake|kebab|camel
 #[get("/text/case/<mode>?<text>")]
-pub fn case(mode: String, text: String) -> String {
+pub fn case(_key: ApiKey, mode: String, text: String) -> Reply {
     if text.len() > MAX_TEXT {
         return too_large_str();
     }
-    match mode.to_lowercase().as_str() {
+    let out = match mode.to_lowercase().as_str() {
         "upper" => text.to_uppercase(),
         "lower" => text.to_lowercase(),
         "title" => text
@@ -98,6 +101,9 @@ pub fn case(mode: String, text: String) -> String {
             }
             out
         }
-        _ => "Error: unsupported case (upper, lower, title, snake, kebab, camel)".to_string(),
-    }
+        _ => {
+            return ApiError::unsupported("unsupported case (upper, lower, title, snake, kebab, camel)").into()
+        }
+    };
+    Reply::text(out)
 }
@@ -1,16 +1,21 @@
+use std::fmt::Write;
 use std::path::PathBuf;
 
-use chrono::{TimeZone, Utc};
-use chrono_tz::Tz;
-use rocket::serde::json::{serde_json, Json, Value};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, Offset, SecondsFormat, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use rocket::serde::json::serde_json;
 
+use crate::auth::ApiKey;
+use crate::dateparse::{self, EpochUnit, Parsed, When};
+use crate::response::{ApiError, Reply};
 use crate::types::{TimeResponse, TimestampResponse};
 
 // Time endpoints
 #[get("/timestamp")]
-pub fn timestamp() -> Json<TimestampResponse> {
+pub fn timestamp(_key: ApiKey) -> Reply {
     let now = Utc::now();
-    Json(TimestampResponse {
+    Reply::json(TimestampResponse {
         seconds: now.timestamp(),
         milliseconds: now.timestamp_millis(),
     })
@@ -19,10 +24,10 @@ pub fn timestamp() -> Json<TimestampResponse> {
 /// Convert a unix timestamp to a human-readable date.
 /// `/timestamp/1781022640` -> formatted UTC + ISO8601. Auto-detects ms vs s.
 #[get("/timestamp/<unix>")]
-pub fn timestamp_to_date(unix: String) -> Json<Value> {
+pub fn timestamp_to_date(_key: ApiKey, unix: String) -> Reply {
     let unix: i64 = match unix.trim().parse() {
         Ok(n) => n,
-        Err(_) => return Json(serde_json::json!({"error": "timestamp must be an integer"})),
+        Err(_) => return ApiError::invalid("timestamp must be an integer").into(),
     };
     // Heuristic: values beyond ~year 5138 in seconds are almost certainly milliseconds.
     let (secs, unit) = if unix.abs() > 100_000_000_000 {
@@ -31,20 +36,20 @@ pub fn timestamp_to_date(unix: String) -> Json<Value> {
         (unix, "seconds")
     };
     match Utc.timestamp_opt(secs, 0).single() {
-        Some(dt) => Json(serde_json::json!({
+        Some(dt) => Reply::json(serde_json::json!({
             "unix": unix,
             "interpreted_as": unit,
             "utc": dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
             "iso8601": dt.to_rfc3339(),
         })),
-        None => Json(serde_json::json!({"error": "invalid timestamp"})),
+        None => ApiError::invalid("invalid timestamp").into(),
     }
 }
 
 #[get("/time/utc")]
-pub fn time_utc() -> Json<TimeResponse> {
+pub fn time_utc(_key: ApiKey) -> Reply {
     let now = Utc::now();
-    Json(TimeResponse {
+    Reply::json(TimeResponse {
         datetime: now.format("%Y-%m-%d %H:%M:%S").to_string(),
         timezone: "UTC".to_string(),
     })
@@ -53,58 +58,330 @@ pub fn time_utc() -> Json<TimeResponse> {
 /// Current time in an IANA timezone, e.g. `/time/America/New_York` or `/time/Asia/Dubai`.
 /// Uses `<tz..>` so multi-segment zone names like `America/New_York` work.
 #[get("/time/<tz..>")]
-pub fn time_tz(tz: PathBuf) -> Json<TimeResponse> {
-    let name = tz.to_string_lossy().to_string();
-    match name.parse::<Tz>() {
+pub fn time_tz(_key: ApiKey, tz: PathBuf) -> Reply {
+    match parse_zone(&tz.to_string_lossy()) {
         Ok(zone) => {
             let now = Utc::now().with_timezone(&zone);
-            Json(TimeResponse {
+            Reply::json(TimeResponse {
                 datetime: now.format("%Y-%m-%d %H:%M:%S").to_string(),
                 timezone: zone.name().to_string(),
             })
         }
-        Err(_) => Json(TimeResponse {
-            datetime: String::new(),
-            timezone: format!(
-                "unknown timezone '{}' (use IANA names like America/New_York or Asia/Dubai)",
-                name
-            ),
-        }),
+        Err(e) => e.into(),
     }
 }
 
+fn parse_zone(name: &str) -> Result<Tz, ApiError> {
+    name.trim().parse::<Tz>().map_err(|_| {
+        ApiError::invalid(format!(
+            "unknown timezone '{}' (use IANA names like America/New_York or Asia/Dubai)",
+            name
+        ))
+    })
+}
+
+const MAX_CONVERT_ZONES: usize = 50;
+pub const MAX_PATTERN: usize = 256;
+
+/// `/time/convert?at=2026-10-17T09:00&from=Asia/Dubai&to=America/New_York,Europe/London`
+/// -> the same instant in every `to` zone with its offset, abbreviation and DST flag.
+/// `at` is anything `/time/parse` reads, local to `from` (default UTC) unless it carries its
+/// own offset; it defaults to now.
+#[get("/time/convert?<at>&<from>&<to>&<disambiguation>")]
+pub fn time_convert(
+    _key: ApiKey,
+    at: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    disambiguation: Option<String>,
+) -> Result<Reply, ApiError> {
+    let targets = match to.as_deref().map(str::trim) {
+        Some(to) if !to.is_empty() => to.split(',').map(parse_zone).collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(ApiError::bad_request("provide ?to=<zone>[,<zone>...], e.g. to=America/New_York,Europe/London")),
+    };
+    if targets.len() > MAX_CONVERT_ZONES {
+        return Err(ApiError::too_large(format!("too many zones (max {})", MAX_CONVERT_ZONES)));
+    }
+    let zone = parse_zone(from.as_deref().unwrap_or("UTC"))?;
+    let at = resolve(at.as_deref(), zone, Utc::now(), EpochUnit::Seconds, parse_disambiguation(disambiguation)?)?;
+    let mut reply = serde_json::json!({
+        "utc": at.instant.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "unix": at.instant.timestamp(),
+        "local_time": at.local_time,
+        "from": zoned(at.instant, zone)?,
+        "to": targets.iter().map(|&tz| zoned(at.instant, tz)).collect::<Result<Vec<_>, _>>()?,
+    });
+    if let Some(note) = at.note {
+        reply["note"] = note.into();
+    }
+    Ok(Reply::json(reply))
+}
+
+/// `/time/parse?at=next friday 9am&tz=Europe/Berlin` -> the instant as RFC 3339 in UTC and
+/// in `tz`, plus the epoch in s, ms, µs and ns. Reads RFC 3339 and 2822, ISO week dates,
+/// `YYYY-MM-DD HH:MM`, relative expressions (counted from `now`, default the current time)
+/// and integers as epochs in `unit` (default seconds; nothing is guessed).
+#[get("/time/parse?<at>&<tz>&<unit>&<now>&<disambiguation>")]
+pub fn time_parse(
+    _key: ApiKey,
+    at: Option<String>,
+    tz: Option<String>,
+    unit: Option<String>,
+    now: Option<String>,
+    disambiguation: Option<String>,
+) -> Result<Reply, ApiError> {
+    let at = match at.as_deref().map(str::trim) {
+        Some(at) if !at.is_empty() => at,
+        _ => return Err(ApiError::bad_request("provide ?at=<date>, e.g. at=2026-10-17T09:00 or at=next friday 9am")),
+    };
+    let zone = parse_zone(tz.as_deref().unwrap_or("UTC"))?;
+    let disambiguation = parse_disambiguation(disambiguation)?;
+    let now = match now.as_deref() {
+        Some(now) => resolve(Some(now), zone, Utc::now(), EpochUnit::Seconds, disambiguation)
+            .map_err(|e| e.prefixed("now"))?
+            .instant,
+        None => Utc::now(),
+    };
+    let resolved = resolve(Some(at), zone, now, parse_unit(unit)?, disambiguation)?;
+    let mut reply = serde_json::json!({
+        "input": at,
+        "format": resolved.format,
+        "utc": resolved.instant.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "local": zoned(resolved.instant, zone)?,
+        "local_time": resolved.local_time,
+        "epoch": epoch(resolved.instant),
+    });
+    if let Some(note) = resolved.note {
+        reply["note"] = note.into();
+    }
+    Ok(Reply::json(reply))
+}
+
+/// `/time/format?pattern=%A %d %B %Y, %H:%M %Z&tz=Asia/Tokyo` -> `at` (default now, anything
+/// `/time/parse` reads) formatted with a strftime pattern in `tz`.
+#[get("/time/format?<pattern>&<at>&<tz>&<unit>&<disambiguation>")]
+pub fn time_format(
+    _key: ApiKey,
+    pattern: Option<String>,
+    at: Option<String>,
+    tz: Option<String>,
+    unit: Option<String>,
+    disambiguation: Option<String>,
+) -> Result<Reply, ApiError> {
+    let pattern = match pattern {
+        Some(p) if !p.is_empty() => p,
+        _ => return Err(ApiError::bad_request("provide ?pattern=<strftime>, e.g. pattern=%Y-%m-%d %H:%M %Z")),
+    };
+    if pattern.len() > MAX_PATTERN {
+        return Err(ApiError::too_large(format!("pattern too large (max {} chars)", MAX_PATTERN)));
+    }
+    let items: Vec<Item> = StrftimeItems::new(&pattern).collect();
+    if items.contains(&Item::Error) {
+        return Err(ApiError::invalid("pattern has an unknown or incomplete % specifier (use %% for a literal %)"));
+    }
+    let zone = parse_zone(tz.as_deref().unwrap_or("UTC"))?;
+    let at = resolve(at.as_deref(), zone, Utc::now(), parse_unit(unit)?, parse_disambiguation(disambiguation)?)?;
+    let mut formatted = String::new();
+    write!(formatted, "{}", in_zone(at.instant, zone)?.format_with_items(items.iter()))
+        .map_err(|_| ApiError::invalid("pattern can't be applied to this date"))?;
+    Ok(Reply::json(serde_json::json!({
+        "formatted": formatted,
+        "pattern": pattern,
+        "timezone": zone.name(),
+        "utc": at.instant.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "epoch": epoch(at.instant),
+    })))
+}
+
+fn parse_unit(unit: Option<String>) -> Result<EpochUnit, ApiError> {
+    unit.as_deref()
+        .map_or(Ok(EpochUnit::Seconds), str::parse)
+        .map_err(|_| ApiError::unsupported("unknown unit (s, ms, us, ns)"))
+}
+
+/// What to do with local times that happen twice or never around DST changes, after Temporal's
+/// `disambiguation`: compatible (the default) takes the earlier of two and moves a skipped
+/// time forward by the gap, earlier/later pick a side, reject turns both into a 422.
+#[derive(Clone, Copy)]
+enum Disambiguation {
+    Compatible,
+    Earlier,
+    Later,
+    Reject,
+}
+
+fn parse_disambiguation(value: Option<String>) -> Result<Disambiguation, ApiError> {
+    match value.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("compatible") => Ok(Disambiguation::Compatible),
+        Some("earlier") => Ok(Disambiguation::Earlier),
+        Some("later") => Ok(Disambiguation::Later),
+        Some("reject") => Ok(Disambiguation::Reject),
+        Some(_) => Err(ApiError::unsupported("unknown disambiguation (compatible, earlier, later, reject)")),
+    }
+}
+
+/// An `at` parameter placed in `zone`.
+struct Resolved {
+    format: &'static str,
+    instant: DateTime<Utc>,
+    /// unique, ambiguous or nonexistent.
+    local_time: &'static str,
+    note: Option<String>,
+}
+
+/// `at` (default now) as an instant; wall-clock inputs are read in `zone` and relative
+/// ones count from `now`.
+fn resolve(
+    at: Option<&str>,
+    zone: Tz,
+    now: DateTime<Utc>,
+    unit: EpochUnit,
+    disambiguation: Disambiguation,
+) -> Result<Resolved, ApiError> {
+    let parsed = match at.map(str::trim) {
+        None | Some("") => Parsed { format: "now", when: When::Instant(now) },
+        Some(at) => dateparse::parse(at, now.with_timezone(&zone), unit).map_err(ApiError::invalid)?,
+    };
+    let (instant, local_time, note) = match parsed.when {
+        When::Instant(instant) => (instant, "unique", None),
+        When::Local(local) => resolve_local(local, zone, disambiguation)?,
+    };
+    Ok(Resolved { format: parsed.format, instant, local_time, note })
+}
+
+fn epoch(instant: DateTime<Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "seconds": instant.timestamp(),
+        "milliseconds": instant.timestamp_millis(),
+        "microseconds": instant.timestamp_micros(),
+        // i64 nanoseconds only reach 1677-2262.
+        "nanoseconds": instant.timestamp_nanos_opt(),
+    })
+}
+
+/// The instant `local` names in `zone`, whether it was unique, ambiguous or nonexistent, and
+/// what was done about it.
+fn resolve_local(
+    local: NaiveDateTime,
+    zone: Tz,
+    disambiguation: Disambiguation,
+) -> Result<(DateTime<Utc>, &'static str, Option<String>), ApiError> {
+    let shown = local.format("%Y-%m-%dT%H:%M:%S");
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(dt) => Ok((dt.with_timezone(&Utc), "unique", None)),
+        LocalResult::Ambiguous(earlier, later) => {
+            let problem = format!(
+                "{} happens twice in {} as clocks go back from {} to {}",
+                shown,
+                zone.name(),
+                earlier.format("%:z %Z"),
+                later.format("%:z %Z")
+            );
+            let (dt, side) = match disambiguation {
+                Disambiguation::Compatible | Disambiguation::Earlier => (earlier, "earlier"),
+                Disambiguation::Later => (later, "later"),
+                Disambiguation::Reject => {
+                    return Err(ApiError::invalid(format!("{}; pick one with ?disambiguation=earlier|later", problem)))
+                }
+            };
+            Ok((dt.with_timezone(&Utc), "ambiguous", Some(format!("{}; using the {} ({})", problem, side, dt.format("%Z")))))
+        }
+        LocalResult::None => {
+            // Offsets a day either side of the gap; a zone doesn't change twice in a day. Wall
+            // times that only miss because they fall off the end of chrono's range land here
+            // too, and have no day on one side.
+            let out_of_range = || ApiError::invalid(format!("{} is out of range in {}", shown, zone.name()));
+            let day = Duration::days(1);
+            let before = zone.offset_from_utc_datetime(&local.checked_sub_signed(day).ok_or_else(out_of_range)?).fix();
+            let after = zone.offset_from_utc_datetime(&local.checked_add_signed(day).ok_or_else(out_of_range)?).fix();
+            let problem = format!(
+                "{} doesn't exist in {}: clocks jump forward from {} to {}",
+                shown,
+                zone.name(),
+                before,
+                after
+            );
+            // Reading the wall time with the offset from before the gap lands after it, and
+            // vice versa, so each is shifted by the gap's length.
+            let (offset, side) = match disambiguation {
+                Disambiguation::Compatible | Disambiguation::Later => (before, "forward"),
+                Disambiguation::Earlier => (after, "back"),
+                Disambiguation::Reject => {
+                    return Err(ApiError::invalid(format!("{}; shift it with ?disambiguation=earlier|later", problem)))
+                }
+            };
+            let utc = local.checked_sub_signed(Duration::seconds(offset.local_minus_utc().into()));
+            let instant = Utc.from_utc_datetime(&utc.ok_or_else(out_of_range)?);
+            let moved = in_zone(instant, zone)?;
+            Ok((
+                instant,
+                "nonexistent",
+                Some(format!("{}; moved {} to {}", problem, side, moved.format("%Y-%m-%dT%H:%M:%S %Z"))),
+            ))
+        }
+    }
+}
+
+/// `instant` as seen in `zone`, or a 422 when that wall-clock time is past chrono's range.
+fn in_zone(instant: DateTime<Utc>, zone: Tz) -> Result<DateTime<Tz>, ApiError> {
+    let offset = zone.offset_from_utc_datetime(&instant.naive_utc()).fix();
+    match instant.naive_utc().checked_add_signed(Duration::seconds(offset.local_minus_utc().into())) {
+        Some(_) => Ok(instant.with_timezone(&zone)),
+        None => Err(ApiError::invalid(format!(
+            "{} is out of range in {}",
+            instant.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            zone.name()
+        ))),
+    }
+}
+
+/// `instant` as seen in `zone`.
+fn zoned(instant: DateTime<Utc>, zone: Tz) -> Result<serde_json::Value, ApiError> {
+    let local = in_zone(instant, zone)?;
+    let offset = local.offset();
+    Ok(serde_json::json!({
+        "timezone": zone.name(),
+        "datetime": local.to_rfc3339_opts(SecondsFormat::AutoSi, false),
+        "utc_offset": local.format("%:z").to_string(),
+        "offset_seconds": offset.fix().local_minus_utc(),
+        "abbreviation": offset.abbreviation(),
+        "dst": !offset.dst_offset().is_zero(),
+    }))
+}
+
 /// Humanize a duration in seconds. `/duration?seconds=90061` -> "1d 1h 1m 1s"
 #[get("/duration?<seconds>")]
-pub fn duration(seconds: Option<String>) -> Json<Value> {
+pub fn duration(_key: ApiKey, seconds: Option<String>) -> Reply {
     let seconds: i64 = match seconds.as_deref().map(str::trim).map(str::parse) {
         Some(Ok(n)) => n,
-        _ => return Json(serde_json::json!({"error": "provide ?seconds=<integer>"})),
+        _ => return ApiError::bad_request("provide ?seconds=<integer>").into(),
     };
     let s = seconds.abs();
     let days = s / 86_400;
     let hours = (s % 86_400) / 3_600;
     let mins = (s % 3_600) / 60;
-    let secs = s % 60;
 
-    let mut parts: Vec<String> = Vec::new();
-    if days > 0 {
-        parts.push(format!("{}d", days));
-    }
-    if hours > 0 {
-        parts.push(format!("{}h", hours));
-    }
-    if mins > 0 {
-        parts.push(format!("{}m", mins));
-    }
-    if secs > 0 || parts.is_empty() {
-        parts.push(format!("{}s", secs));
-    }
-
-    Json(serde_json::json!({
+    Reply::json(serde_json::json!({
         "seconds": seconds,
-        "human": parts.join(" "),
+        "human": humanize(seconds),
         "days": days,
         "hours": hours,
         "minutes": mins,
     }))
 }
+
+/// "1d 1h 1m 1s"-style rendering of a (possibly negative) number of seconds; sign is dropped.
+pub fn humanize(seconds: i64) -> String {
+    let s = seconds.unsigned_abs();
+    let units = [(s / 86_400, "d"), ((s % 86_400) / 3_600, "h"), ((s % 3_600) / 60, "m"), (s % 60, "s")];
+    let parts: Vec<String> = units
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, u)| format!("{}{}", n, u))
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
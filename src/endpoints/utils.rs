@@ -3,16 +3,19 @@ use std::time::Duration;
 use rocket::response::status;
 use rocket::tokio::time::sleep;
 
+use crate::auth::ApiKey;
+use crate::response::Reply;
+
 // Utility endpoints
 #[get("/delay/<seconds>")]
-pub async fn delay(seconds: u64) -> &'static str {
+pub async fn delay(_key: ApiKey, seconds: u64) -> Reply {
     // Limit delay to prevent DoS attacks
     let max_delay = cmp::min(seconds, 30); // Max 30 seconds
     sleep(Duration::from_secs(max_delay)).await;
-    "OK"
+    Reply::text("OK")
 }
 
 #[get("/status/<code>")]
-pub fn status_code(code: u16) -> status::Custom<&'static str> {
+pub fn status_code(_key: ApiKey, code: u16) -> status::Custom<&'static str> {
     status::Custom(rocket::http::Status::from_code(code).unwrap_or(rocket::http::Status::Ok), "")
 }
@@ -1,7 +1,14 @@
+use std::collections::HashMap;
 use std::net::IpAddr;
+use rocket::data::{self, Data, FromData, Limits};
+use rocket::form::{error::ErrorKind, Form};
 use rocket::figment::value::Map;
 use rocket::request::{FromRequest, Request};
-use crate::types::WhoamiResponse;
+use rocket::serde::json::serde_json;
+use rocket::serde::Deserialize;
+use crate::proxy::ProxyConfig;
+use crate::response::ApiError;
+use crate::types::{EchoResponse, WhoamiResponse};
 
 // Helper struct for getting client IP
 #[derive(Debug)]
@@ -12,38 +19,31 @@ impl<'r> FromRequest<'r> for ClientIp {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
-        let default_ip: IpAddr = "127.0.0.1".parse().unwrap();
-        
-        // Prioritize x-real-ip header, then fall back to other methods
-        let client_ip = request
-            .headers()
-            .get_one("x-real-ip")
-            .and_then(|ip| {
-                // Validate it's a proper IP address
-                ip.trim().parse::<IpAddr>().ok()?.to_string().into()
-            })
-            .or_else(|| {
-                // Fall back to Rocket's built-in remote address when available
-                request.remote().map(|addr| addr.ip().to_string())
-            })
-            .or_else(|| {
-                // Finally fall back to x-forwarded-for header
-                request
-                    .headers()
-                    .get_one("x-forwarded-for")
-                    .and_then(|ip| {
-                        // Take only the first IP from comma-separated list
-                        let first_ip = ip.split(',').next()?.trim();
-                        // Validate it's a proper IP address
-                        first_ip.parse::<IpAddr>().ok()?.to_string().into()
-                    })
-            })
-            .unwrap_or_else(|| default_ip.to_string());
-        
-        rocket::request::Outcome::Success(ClientIp(client_ip))
+        rocket::request::Outcome::Success(ClientIp(resolve_ip(request).to_string()))
     }
 }
 
+// Single source of truth for the client address: the trusted-proxy resolver from
+// proxy.rs, or its defaults when the config fairing isn't attached.
+pub fn resolve_ip(request: &Request<'_>) -> IpAddr {
+    match request.rocket().state::<ProxyConfig>() {
+        Some(config) => config.client_ip(request),
+        None => ProxyConfig::default().client_ip(request),
+    }
+}
+
+/// API key sent as `Authorization: Bearer <key>` or `X-API-Key: <key>`, if any.
+pub fn api_key<'r>(request: &'r Request<'_>) -> Option<&'r str> {
+    let bearer = request
+        .headers()
+        .get_one("authorization")
+        .and_then(|v| v.strip_prefix("Bearer ").or_else(|| v.strip_prefix("bearer ")));
+    bearer
+        .or_else(|| request.headers().get_one("x-api-key"))
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+}
+
 // Helper struct for getting all headers (used by general endpoints)
 #[derive(Debug)]
 pub struct AllHeaders(pub Map<String, String>);
@@ -65,20 +65,53 @@ impl<'r> FromRequest<'r> for AllHeaders {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
-        let mut headers_map: Map<String, String> = Map::new();
-        
-        for h in request.headers().iter() {
-            let header_name = h.name().as_str().to_lowercase();
-            // SECURITY: Filter out sensitive headers and proxy/infrastructure headers for general endpoints
-            if !SENSITIVE_HEADERS.contains(&header_name.as_str()) 
-                && !header_name.starts_with("x-")
-                && !header_name.starts_with("cf-")
-                && !header_name.starts_with("cdn-") {
-                headers_map.insert(h.name().to_string(), h.value().to_string());
-            }
+        rocket::request::Outcome::Success(AllHeaders(public_headers(request)))
+    }
+}
+
+fn public_headers(request: &Request<'_>) -> Map<String, String> {
+    let mut headers_map: Map<String, String> = Map::new();
+
+    for h in request.headers().iter() {
+        let header_name = h.name().as_str().to_lowercase();
+        // SECURITY: Filter out sensitive headers and proxy/infrastructure headers for general endpoints
+        if !SENSITIVE_HEADERS.contains(&header_name.as_str())
+            && !header_name.starts_with("x-")
+            && !header_name.starts_with("cf-")
+            && !header_name.starts_with("cdn-") {
+            headers_map.insert(h.name().to_string(), h.value().to_string());
         }
-        
-        rocket::request::Outcome::Success(AllHeaders(headers_map))
+    }
+
+    headers_map
+}
+
+// FromRequest implementation for EchoResponse: everything except the body,
+// which the /echo handlers fill in for methods that carry a payload.
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for EchoResponse {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
+        let query: HashMap<String, String> = request
+            .uri()
+            .query()
+            .map(|q| {
+                q.segments()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        rocket::request::Outcome::Success(EchoResponse {
+            method: request.method().as_str().to_string(),
+            query,
+            headers: public_headers(request),
+            content_type: request.content_type().map(|ct| ct.to_string()),
+            content_length: request.headers().get_one("content-length").and_then(|v| v.trim().parse().ok()),
+            body: String::new(),
+            body_encoding: "utf8".to_string(),
+        })
     }
 }
 
@@ -113,33 +146,7 @@ impl<'r> FromRequest<'r> for WhoamiResponse {
             cookie_map.insert(c.name().to_string(), c.value().to_string());
         }
 
-        let default_ip: IpAddr = "127.0.0.1".parse().unwrap();
-
-        // Use same IP extraction logic as ClientIp - prioritize x-real-ip
-        let remote_ip = request
-            .headers()
-            .get_one("x-real-ip")
-            .and_then(|ip| {
-                // Validate it's a proper IP address
-                ip.trim().parse::<IpAddr>().ok()
-            })
-            .or_else(|| {
-                // Fall back to Rocket's built-in remote address when available
-                request.remote().map(|addr| addr.ip())
-            })
-            .or_else(|| {
-                // Finally fall back to x-forwarded-for header
-                request
-                    .headers()
-                    .get_one("x-forwarded-for")
-                    .and_then(|ip| {
-                        // Take only the first IP from comma-separated list
-                        let first_ip = ip.split(',').next()?.trim();
-                        // Validate it's a proper IP address
-                        first_ip.parse::<IpAddr>().ok()
-                    })
-            })
-            .unwrap_or(default_ip);
+        let remote_ip = resolve_ip(request);
 
         rocket::request::Outcome::Success(WhoamiResponse {
             ip: remote_ip.to_string(),
@@ -149,3 +156,92 @@ impl<'r> FromRequest<'r> for WhoamiResponse {
         })
     }
 }
+
+// Helper struct for POST bodies: either the raw bytes as sent, or the `input`
+// field of a `{"input": ...}` JSON body. Capped by the `bytes`/`json` limits.
+#[derive(Debug)]
+pub struct BodyInput(pub Vec<u8>);
+
+#[derive(Deserialize)]
+struct JsonInput {
+    input: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for BodyInput {
+    type Error = ApiError;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let is_json = request.content_type().is_some_and(|ct| ct.is_json());
+        let limit = if is_json {
+            request.limits().get("json").unwrap_or(Limits::JSON)
+        } else {
+            request.limits().get("bytes").unwrap_or(Limits::BYTES)
+        };
+
+        let bytes = match data.open(limit).into_bytes().await {
+            Ok(bytes) if bytes.is_complete() => bytes.into_inner(),
+            Ok(_) => {
+                let error = ApiError::too_large(format!("body too large (max {})", limit));
+                return data::Outcome::Error((error.status(), error));
+            }
+            Err(e) => {
+                let error = ApiError::bad_request(e.to_string());
+                return data::Outcome::Error((error.status(), error));
+            }
+        };
+
+        if !is_json {
+            return data::Outcome::Success(BodyInput(bytes));
+        }
+        match serde_json::from_slice::<JsonInput>(&bytes) {
+            Ok(body) => data::Outcome::Success(BodyInput(body.input.into_bytes())),
+            Err(_) => {
+                let error = ApiError::invalid("expected a JSON body like {\"input\": \"...\"}");
+                data::Outcome::Error((error.status(), error))
+            }
+        }
+    }
+}
+
+#[derive(FromForm)]
+struct ImageForm<'r> {
+    image: &'r [u8],
+}
+
+// Helper struct for image uploads: the raw body (`Content-Type: image/png` or anything
+// else), or the `image` field of a multipart form. The file itself is capped by the
+// `bytes` limit and a whole form by `data-form`.
+#[derive(Debug)]
+pub struct ImageUpload(pub Vec<u8>);
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for ImageUpload {
+    type Error = ApiError;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let limit = request.limits().get("bytes").unwrap_or(Limits::BYTES);
+        if !request.content_type().is_some_and(|ct| ct.is_form_data()) {
+            let error = match data.open(limit).into_bytes().await {
+                Ok(bytes) if bytes.is_complete() => return data::Outcome::Success(ImageUpload(bytes.into_inner())),
+                Ok(_) => ApiError::too_large(format!("image too large (max {})", limit)),
+                Err(e) => ApiError::bad_request(e.to_string()),
+            };
+            return data::Outcome::Error((error.status(), error));
+        }
+        match Form::<ImageForm<'r>>::from_data(request, data).await {
+            data::Outcome::Success(form) => data::Outcome::Success(ImageUpload(form.into_inner().image.to_vec())),
+            data::Outcome::Forward(f) => data::Outcome::Forward(f),
+            data::Outcome::Error((_, errors)) => {
+                let error = if errors.iter().any(|e| matches!(e.kind, ErrorKind::InvalidLength { .. })) {
+                    ApiError::too_large(format!("image too large (max {})", limit))
+                } else if errors.iter().any(|e| matches!(e.kind, ErrorKind::Missing)) {
+                    ApiError::bad_request("multipart uploads need an `image` file field")
+                } else {
+                    ApiError::bad_request(errors.to_string())
+                };
+                data::Outcome::Error((error.status(), error))
+            }
+        }
+    }
+}
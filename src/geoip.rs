@@ -0,0 +1,106 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use rocket::fairing::AdHoc;
+use rocket::serde::Deserialize;
+
+use crate::types::IpInfoResponse;
+
+// Offline IP geolocation backed by local MMDB files (MaxMind GeoLite2, DB-IP, IPinfo...).
+// Configured under `[default.geoip]` in Rocket.toml; both paths are optional and may
+// point at the same file when a single database carries city and ASN data.
+#[derive(Deserialize, Default)]
+struct GeoIpConfig {
+    city_db: Option<PathBuf>,
+    asn_db: Option<PathBuf>,
+}
+
+pub struct GeoIp {
+    city: Option<Reader<Vec<u8>>>,
+    asn: Option<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    /// Loads the configured databases once at ignition and manages them as state.
+    /// Malformed config or a configured-but-unreadable file aborts launch rather than
+    /// silently serving nulls.
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("GeoIP database", |rocket| async {
+            let config = match rocket.figment().extract_inner::<GeoIpConfig>("geoip") {
+                Ok(config) => config,
+                Err(e) if e.missing() => GeoIpConfig::default(),
+                Err(e) => {
+                    error!("invalid [geoip] config: {}", e);
+                    return Err(rocket);
+                }
+            };
+            let open = |path: &Option<PathBuf>| -> Result<Option<Reader<Vec<u8>>>, String> {
+                match path {
+                    Some(p) => Reader::open_readfile(p)
+                        .map(Some)
+                        .map_err(|e| format!("{}: {}", p.display(), e)),
+                    None => Ok(None),
+                }
+            };
+            match (open(&config.city_db), open(&config.asn_db)) {
+                (Ok(city), Ok(asn)) => Ok(rocket.manage(GeoIp { city, asn })),
+                (Err(e), _) | (_, Err(e)) => {
+                    error!("failed to open GeoIP database {}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.city.is_some() || self.asn.is_some()
+    }
+
+    /// Look up `ip` in every configured database. Addresses missing from a database
+    /// (private ranges, unallocated space) simply leave its fields as `None`.
+    pub fn lookup(&self, ip: IpAddr) -> Result<IpInfoResponse, String> {
+        let mut info = IpInfoResponse {
+            ip: ip.to_string(),
+            city: None,
+            region: None,
+            country: None,
+            asn: None,
+            org: None,
+        };
+
+        if let Some(reader) = &self.city {
+            match reader.lookup::<geoip2::City>(ip) {
+                Ok(city) => {
+                    info.city = city.city.and_then(|c| english_name(c.names));
+                    info.region = city
+                        .subdivisions
+                        .and_then(|s| s.into_iter().next())
+                        .and_then(|s| english_name(s.names));
+                    info.country = city
+                        .country
+                        .and_then(|c| c.iso_code.map(str::to_string).or_else(|| english_name(c.names)));
+                }
+                Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        if let Some(reader) = &self.asn {
+            match reader.lookup::<geoip2::Asn>(ip) {
+                Ok(asn) => {
+                    info.asn = asn.autonomous_system_number.map(|n| format!("AS{}", n));
+                    info.org = asn.autonomous_system_organization.map(str::to_string);
+                }
+                Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        Ok(info)
+    }
+}
+
+fn english_name(names: Option<std::collections::BTreeMap<&str, &str>>) -> Option<String> {
+    names.and_then(|n| n.get("en").map(|s| s.to_string()))
+}
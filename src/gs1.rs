@@ -0,0 +1,233 @@
+// GS1 element strings in their human-readable form, e.g. `(01)09501101530003(17)261231(10)AB12`.
+// Only the Application Identifiers below are accepted; each is checked for length, charset,
+// dates and check digits before anything is encoded, since a scanner will happily read a
+// malformed label and the error only shows up in someone else's warehouse system.
+
+/// Which values an AI takes.
+#[derive(Clone, Copy)]
+enum Kind {
+    Numeric,
+    /// Digits, the last a GS1 mod-10 check digit.
+    CheckDigit,
+    /// YYMMDD; a day of 00 means the end of the month.
+    Date,
+    /// GS1 AI encodable character set 82.
+    Alphanumeric,
+}
+
+#[derive(Clone, Copy)]
+enum Len {
+    Fixed(usize),
+    Max(usize),
+}
+
+struct Ai {
+    /// AI digits; a trailing `n` stands for a decimal-point position 0-9.
+    ai: &'static str,
+    title: &'static str,
+    len: Len,
+    kind: Kind,
+}
+
+use Kind::*;
+use Len::*;
+
+const fn ai(ai: &'static str, title: &'static str, len: Len, kind: Kind) -> Ai {
+    Ai { ai, title, len, kind }
+}
+
+const AIS: &[Ai] = &[
+    ai("00", "SSCC", Fixed(18), CheckDigit),
+    ai("01", "GTIN", Fixed(14), CheckDigit),
+    ai("02", "CONTENT", Fixed(14), CheckDigit),
+    ai("10", "BATCH/LOT", Max(20), Alphanumeric),
+    ai("11", "PROD DATE", Fixed(6), Date),
+    ai("12", "DUE DATE", Fixed(6), Date),
+    ai("13", "PACK DATE", Fixed(6), Date),
+    ai("15", "BEST BEFORE", Fixed(6), Date),
+    ai("16", "SELL BY", Fixed(6), Date),
+    ai("17", "USE BY", Fixed(6), Date),
+    ai("20", "VARIANT", Fixed(2), Numeric),
+    ai("21", "SERIAL", Max(20), Alphanumeric),
+    ai("22", "CPV", Max(20), Alphanumeric),
+    ai("240", "ADDITIONAL ID", Max(30), Alphanumeric),
+    ai("241", "CUST. PART No.", Max(30), Alphanumeric),
+    ai("250", "SECONDARY SERIAL", Max(30), Alphanumeric),
+    ai("251", "REF. TO SOURCE", Max(30), Alphanumeric),
+    ai("30", "VAR. COUNT", Max(8), Numeric),
+    ai("310n", "NET WEIGHT (kg)", Fixed(6), Numeric),
+    ai("311n", "LENGTH (m)", Fixed(6), Numeric),
+    ai("312n", "WIDTH (m)", Fixed(6), Numeric),
+    ai("313n", "HEIGHT (m)", Fixed(6), Numeric),
+    ai("314n", "AREA (m2)", Fixed(6), Numeric),
+    ai("315n", "NET VOLUME (l)", Fixed(6), Numeric),
+    ai("316n", "NET VOLUME (m3)", Fixed(6), Numeric),
+    ai("320n", "NET WEIGHT (lb)", Fixed(6), Numeric),
+    ai("330n", "GROSS WEIGHT (kg)", Fixed(6), Numeric),
+    ai("37", "COUNT", Max(8), Numeric),
+    ai("400", "ORDER NUMBER", Max(30), Alphanumeric),
+    ai("401", "GINC", Max(30), Alphanumeric),
+    ai("402", "GSIN", Fixed(17), CheckDigit),
+    ai("403", "ROUTE", Max(30), Alphanumeric),
+    ai("410", "SHIP TO LOC", Fixed(13), CheckDigit),
+    ai("411", "BILL TO", Fixed(13), CheckDigit),
+    ai("412", "PURCHASE FROM", Fixed(13), CheckDigit),
+    ai("413", "SHIP FOR LOC", Fixed(13), CheckDigit),
+    ai("414", "LOC No.", Fixed(13), CheckDigit),
+    ai("415", "PAY TO", Fixed(13), CheckDigit),
+    ai("420", "SHIP TO POST", Max(20), Alphanumeric),
+    ai("422", "ORIGIN", Fixed(3), Numeric),
+    ai("7003", "EXPIRY TIME", Fixed(10), Numeric),
+    ai("8004", "GIAI", Max(30), Alphanumeric),
+    ai("8020", "REF No.", Max(25), Alphanumeric),
+    ai("90", "INTERNAL", Max(30), Alphanumeric),
+    ai("91", "INTERNAL", Max(90), Alphanumeric),
+    ai("92", "INTERNAL", Max(90), Alphanumeric),
+    ai("93", "INTERNAL", Max(90), Alphanumeric),
+    ai("94", "INTERNAL", Max(90), Alphanumeric),
+    ai("95", "INTERNAL", Max(90), Alphanumeric),
+    ai("96", "INTERNAL", Max(90), Alphanumeric),
+    ai("97", "INTERNAL", Max(90), Alphanumeric),
+    ai("98", "INTERNAL", Max(90), Alphanumeric),
+    ai("99", "INTERNAL", Max(90), Alphanumeric),
+];
+
+/// AIs whose element length is fixed by the standard, so no FNC1 separator follows them.
+/// Keyed by the first two AI digits (GS1 General Specifications, figure 7.8.5-2).
+const PREDEFINED_LENGTH: &[&str] =
+    &["00", "01", "02", "03", "04", "11", "12", "13", "14", "15", "16", "17", "18", "19", "20", "31", "32", "33", "34", "35", "36", "41"];
+
+/// One parsed `(AI)value` pair.
+pub struct Element {
+    pub ai: String,
+    pub value: String,
+}
+
+/// Splits `(AI)value...` and validates every element.
+pub fn parse(input: &str) -> Result<Vec<Element>, String> {
+    let mut elements = Vec::new();
+    let mut rest = input.trim();
+    if !rest.starts_with('(') {
+        return Err("GS1 data must be written as (AI)value pairs, e.g. (01)09501101530003(10)ABC".to_string());
+    }
+    while !rest.is_empty() {
+        let close = rest.find(')').ok_or("unclosed ( in GS1 data")?;
+        let ai = &rest[1..close];
+        rest = &rest[close + 1..];
+        // The value runs to the next "(digits)", so values may still contain parentheses.
+        let end = rest.char_indices().map(|(i, _)| i).find(|&i| ai_at(&rest[i..])).unwrap_or(rest.len());
+        elements.push(Element { ai: ai.to_string(), value: rest[..end].to_string() });
+        rest = &rest[end..];
+    }
+    for element in &elements {
+        validate(element)?;
+    }
+    Ok(elements)
+}
+
+/// Splits an element string as scanned, with `separator` after variable-length elements,
+/// back into validated elements. The inverse of `encode`.
+pub fn parse_encoded(input: &str, separator: char) -> Result<Vec<Element>, String> {
+    let mut elements = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        // AIs are prefix-free, so the shortest known prefix is the AI.
+        let (ai, entry) = (2..=4)
+            .filter_map(|n| rest.get(..n))
+            .find_map(|ai| lookup(ai).map(|entry| (ai, entry)))
+            .ok_or_else(|| format!("unknown or unsupported GS1 AI at \"{}\"", rest.chars().take(4).collect::<String>()))?;
+        rest = &rest[ai.len()..];
+        let end = match entry.len {
+            Fixed(n) => rest.char_indices().nth(n).map_or(rest.len(), |(i, _)| i),
+            Max(_) => rest.find(separator).unwrap_or(rest.len()),
+        };
+        elements.push(Element { ai: ai.to_string(), value: rest[..end].to_string() });
+        rest = rest[end..].strip_prefix(separator).unwrap_or(&rest[end..]);
+    }
+    for element in &elements {
+        validate(element)?;
+    }
+    Ok(elements)
+}
+
+fn ai_at(s: &str) -> bool {
+    s.starts_with('(')
+        && s.find(')').is_some_and(|close| (3..=5).contains(&close) && s[1..close].bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn lookup(ai: &str) -> Option<&'static Ai> {
+    AIS.iter().find(|entry| match entry.ai.strip_suffix('n') {
+        Some(prefix) => ai.len() == prefix.len() + 1 && ai.starts_with(prefix) && ai.as_bytes()[prefix.len()].is_ascii_digit(),
+        None => entry.ai == ai,
+    })
+}
+
+fn validate(element: &Element) -> Result<(), String> {
+    let Element { ai, value } = element;
+    let entry = lookup(ai).ok_or_else(|| format!("unknown or unsupported GS1 AI ({})", ai))?;
+    let what = format!("AI ({}) {}", ai, entry.title);
+    match entry.len {
+        Fixed(n) if value.len() != n => return Err(format!("{} must be exactly {} characters", what, n)),
+        Max(n) if value.is_empty() || value.len() > n => return Err(format!("{} must be 1-{} characters", what, n)),
+        _ => {}
+    }
+    let digits = value.bytes().all(|b| b.is_ascii_digit());
+    match entry.kind {
+        Numeric | CheckDigit | Date if !digits => Err(format!("{} must be digits only", what)),
+        CheckDigit => {
+            let (body, check) = value.split_at(value.len() - 1);
+            let expected = check_digit(body);
+            if check.as_bytes()[0] - b'0' != expected {
+                return Err(format!("{} has check digit {}, expected {}", what, check, expected));
+            }
+            Ok(())
+        }
+        Date => {
+            let month: u32 = value[2..4].parse().unwrap_or(0);
+            let day: u32 = value[4..6].parse().unwrap_or(99);
+            if !(1..=12).contains(&month) || day > 31 {
+                return Err(format!("{} must be a YYMMDD date", what));
+            }
+            Ok(())
+        }
+        Alphanumeric if !value.bytes().all(is_cset82) => {
+            Err(format!("{} has characters outside the GS1 character set", what))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// GS1 AI encodable character set 82: `!"%&'()*+,-./0-9:;<=>?A-Z_a-z`.
+fn is_cset82(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!\"%&'()*+,-./:;<=>?_".contains(&b)
+}
+
+/// GS1 mod-10 check digit for `digits` (weights 3,1,3,... from the right).
+pub fn check_digit(digits: &str) -> u8 {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// The `(AI)value` text printed under the symbol.
+pub fn human_readable(elements: &[Element]) -> String {
+    elements.iter().map(|e| format!("({}){}", e.ai, e.value)).collect()
+}
+
+/// The element string as encoded, with `separator` (FNC1 or GS) after each variable-length
+/// element that isn't last.
+pub fn encode(elements: &[Element], separator: char) -> String {
+    let mut out = String::new();
+    for (i, e) in elements.iter().enumerate() {
+        out.push_str(&e.ai);
+        out.push_str(&e.value);
+        if i + 1 < elements.len() && !PREDEFINED_LENGTH.contains(&&e.ai[..2]) {
+            out.push(separator);
+        }
+    }
+    out
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use ipnet::IpNet;
+use rocket::fairing::AdHoc;
+use rocket::http::{ContentType, Status};
+use rocket::serde::Deserialize;
+use rocket::{Request, Response, State};
+
+use crate::extractors::ClientIp;
+use crate::ratelimit::rerouted;
+use crate::response::ApiError;
+
+// Prometheus metrics at `/metrics`, configured under `[default.metrics]` in Rocket.toml.
+// Requests are labelled by mounted route template (e.g. `/hash/<algo>/<text>`), not the
+// raw path, so label cardinality stays bounded. The endpoint is only served to client
+// IPs (resolved through trusted proxies) inside `allow`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub allow: Vec<IpNet>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            enabled: true,
+            allow: vec!["127.0.0.0/8".parse().unwrap(), "::1/128".parse().unwrap()],
+        }
+    }
+}
+
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+const SIZE_BUCKETS: &[f64] = &[64.0, 256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0];
+
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Histogram {
+        Histogram { bounds, counts: vec![0; bounds.len()], sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if let Some(i) = self.bounds.iter().position(|&b| value <= b) {
+            self.counts[i] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0;
+        for (bound, n) in self.bounds.iter().zip(&self.counts) {
+            cumulative += n;
+            let _ = writeln!(out, "{}_bucket{{{},le=\"{}\"}} {}", name, labels, bound, cumulative);
+        }
+        let _ = writeln!(out, "{}_bucket{{{},le=\"+Inf\"}} {}", name, labels, self.count);
+        let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, self.sum);
+        let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, self.count);
+    }
+}
+
+struct RouteStats {
+    statuses: BTreeMap<u16, u64>,
+    latency: Histogram,
+    size: Histogram,
+    /// Inputs rejected for exceeding a size cap (413), e.g. MAX_TEXT / MAX_ENC / MAX_QR_TEXT.
+    oversized: u64,
+}
+
+pub struct Metrics {
+    allow: Vec<IpNet>,
+    /// Keyed by (route template, method).
+    routes: Mutex<BTreeMap<(String, String), RouteStats>>,
+}
+
+struct RequestStart(Instant);
+
+impl Metrics {
+    /// When enabled, attaches the timing hooks and mounts `/metrics`.
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Metrics", |rocket| async {
+            let config = match rocket.figment().extract_inner::<MetricsConfig>("metrics") {
+                Ok(config) => config,
+                Err(e) if e.missing() => MetricsConfig::default(),
+                Err(e) => {
+                    error!("invalid [metrics] config: {}", e);
+                    return Err(rocket);
+                }
+            };
+            if !config.enabled {
+                return Ok(rocket);
+            }
+            let metrics = Metrics { allow: config.allow, routes: Mutex::new(BTreeMap::new()) };
+            Ok(rocket
+                .manage(metrics)
+                .mount("/", routes![metrics])
+                .attach(AdHoc::on_request("Metrics: start timer", |req, _| {
+                    Box::pin(async move {
+                        req.local_cache(|| RequestStart(Instant::now()));
+                    })
+                }))
+                .attach(AdHoc::on_response("Metrics: record", |req, res| {
+                    Box::pin(async move {
+                        if let Some(metrics) = req.rocket().state::<Metrics>() {
+                            metrics.record(req, res);
+                        }
+                    })
+                })))
+        })
+    }
+
+    fn record(&self, req: &Request<'_>, res: &Response<'_>) {
+        let RequestStart(start) = req.local_cache(|| RequestStart(Instant::now()));
+        let (route, method) = match rerouted(req) {
+            Some(original) => (original.route.as_deref().unwrap_or("unmatched"), original.method),
+            None => (req.route().map_or("unmatched", |r| r.uri.path()), req.method()),
+        };
+        let (route, method) = (route.to_string(), method.as_str().to_string());
+        let status = res.status().code;
+
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = routes.entry((route, method)).or_insert_with(|| RouteStats {
+            statuses: BTreeMap::new(),
+            latency: Histogram::new(LATENCY_BUCKETS),
+            size: Histogram::new(SIZE_BUCKETS),
+            oversized: 0,
+        });
+        *stats.statuses.entry(status).or_default() += 1;
+        stats.latency.observe(start.elapsed().as_secs_f64());
+        // Streamed bodies have no size up front; they're counted but not sized.
+        if let Some(size) = res.body().preset_size() {
+            stats.size.observe(size as f64);
+        }
+        if status == Status::PayloadTooLarge.code {
+            stats.oversized += 1;
+        }
+    }
+
+    fn render(&self) -> String {
+        let routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+
+        out.push_str("# HELP tools_http_requests_total Requests by route, method and status.\n");
+        out.push_str("# TYPE tools_http_requests_total counter\n");
+        for ((route, method), stats) in routes.iter() {
+            for (status, n) in &stats.statuses {
+                let _ = writeln!(
+                    out,
+                    "tools_http_requests_total{{{},status=\"{}\"}} {}",
+                    labels(route, method),
+                    status,
+                    n
+                );
+            }
+        }
+
+        out.push_str("# HELP tools_http_request_duration_seconds Time from request to response.\n");
+        out.push_str("# TYPE tools_http_request_duration_seconds histogram\n");
+        for ((route, method), stats) in routes.iter() {
+            stats.latency.render(&mut out, "tools_http_request_duration_seconds", &labels(route, method));
+        }
+
+        out.push_str("# HELP tools_http_response_size_bytes Response body size.\n");
+        out.push_str("# TYPE tools_http_response_size_bytes histogram\n");
+        for ((route, method), stats) in routes.iter() {
+            stats.size.render(&mut out, "tools_http_response_size_bytes", &labels(route, method));
+        }
+
+        out.push_str("# HELP tools_oversized_inputs_total Inputs rejected for exceeding a size cap.\n");
+        out.push_str("# TYPE tools_oversized_inputs_total counter\n");
+        for ((route, method), stats) in routes.iter().filter(|(_, s)| s.oversized > 0) {
+            let _ = writeln!(out, "tools_oversized_inputs_total{{{}}} {}", labels(route, method), stats.oversized);
+        }
+        out
+    }
+}
+
+fn labels(route: &str, method: &str) -> String {
+    let escape = |v: &str| v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("route=\"{}\",method=\"{}\"", escape(route), escape(method))
+}
+
+/// Prometheus text exposition format.
+#[get("/metrics")]
+fn metrics(client_ip: ClientIp, metrics: &State<Metrics>) -> Result<(ContentType, String), ApiError> {
+    let allowed = client_ip
+        .0
+        .parse::<IpAddr>()
+        .is_ok_and(|ip| metrics.allow.iter().any(|net| net.contains(&ip)));
+    if !allowed {
+        return Err(ApiError::new(Status::Forbidden, "forbidden", "metrics are not served to this address"));
+    }
+    let content_type = ContentType::new("text", "plain").with_params(("version", "0.0.4"));
+    Ok((content_type, metrics.render()))
+}
@@ -0,0 +1,821 @@
+use std::collections::BTreeMap;
+
+use rocket::fairing::AdHoc;
+use rocket::route::Route;
+use rocket::serde::json::serde_json::{json, Map};
+use rocket::serde::json::Value;
+
+use crate::auth::{ApiKeys, PUBLIC_ROUTES};
+use crate::aztec;
+use crate::endpoints::crypto::{MAX_HASH_BODY, MAX_HASH_INPUT};
+use crate::endpoints::encoding::MAX_ENC;
+use crate::endpoints::labels::{MAX_LABELS, MAX_LABEL_GRID, MAX_LABEL_TEXT};
+use crate::endpoints::qr::{
+    MAX_AZTEC_TEXT, MAX_BARCODE_TEXT, MAX_BAR_HEIGHT, MAX_DM_TEXT, MAX_IMAGE_PX, MAX_PDF417_TEXT, MAX_QR_MARGIN, MAX_QR_TEXT,
+    MAX_SCALE,
+};
+use crate::endpoints::text::MAX_TEXT;
+use crate::endpoints::time::MAX_PATTERN;
+use crate::pdf417;
+
+// OpenAPI 3 document served at `/openapi.json` (and rendered by `/docs`). Paths, methods
+// and parameter names come from Rocket's mounted route table at ignition, so they can't
+// drift; `ROUTE_DOCS` adds what Rocket doesn't know: summaries, parameter types, size
+// limits and response shapes. A route missing from `ROUTE_DOCS` still appears, bare.
+pub struct ApiSpec(pub Value);
+
+impl ApiSpec {
+    pub fn fairing() -> AdHoc {
+        AdHoc::on_ignite("OpenAPI spec", |rocket| async {
+            let secured = rocket.state::<ApiKeys>().is_some_and(ApiKeys::is_enabled);
+            let spec = build(rocket.routes(), secured);
+            rocket.manage(ApiSpec(spec))
+        })
+    }
+}
+
+/// Endpoint module (OpenAPI tag) a handler belongs to, by handler name.
+pub fn module_of(name: &str) -> Option<&'static str> {
+    ROUTE_DOCS.iter().find(|d| d.name == name).map(|d| d.tag)
+}
+
+#[derive(Clone, Copy)]
+enum Ty {
+    Str,
+    MaxLen(usize),
+    Int,
+    Num,
+    Range(i64, i64),
+    Bool,
+    OneOf(&'static [&'static str]),
+}
+
+struct Param {
+    name: &'static str,
+    ty: Ty,
+    required: bool,
+    doc: &'static str,
+}
+
+/// Required parameter (path parameters are always required).
+const fn p(name: &'static str, ty: Ty, doc: &'static str) -> Param {
+    Param { name, ty, required: true, doc }
+}
+
+/// Optional query parameter.
+const fn opt(name: &'static str, ty: Ty, doc: &'static str) -> Param {
+    Param { name, ty, required: false, doc }
+}
+
+enum Input {
+    None,
+    /// Raw bytes or `{"input": ...}` JSON, see `extractors::BodyInput`.
+    Body(usize),
+    /// JSON body matching a component schema.
+    Json(&'static str),
+    /// Anything; echoed back.
+    Any,
+    /// A PNG or JPEG, raw or as the `image` field of a multipart form.
+    Image,
+}
+
+enum Output {
+    /// Plain text, or `{"result": ...}` when JSON/YAML is negotiated.
+    Text,
+    /// JSON matching a component schema, or YAML / `key: value` text.
+    Json(&'static str),
+    /// SVG by default, PNG or Unicode text with `?format=`.
+    Image,
+    /// SVG by default, PDF with `?format=pdf` (`/labels`).
+    Document,
+    Html,
+    /// Status code only (`/status/<code>`).
+    Empty,
+}
+
+struct RouteDoc {
+    /// Handler function name, as Rocket records it.
+    name: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    params: &'static [Param],
+    input: Input,
+    output: Output,
+}
+
+const fn route(name: &'static str, tag: &'static str, summary: &'static str, output: Output) -> RouteDoc {
+    RouteDoc { name, tag, summary, params: &[], input: Input::None, output }
+}
+
+impl RouteDoc {
+    const fn with(self, params: &'static [Param]) -> RouteDoc {
+        RouteDoc { params, ..self }
+    }
+
+    const fn body(self, input: Input) -> RouteDoc {
+        RouteDoc { input, ..self }
+    }
+}
+
+const RAW: Param = opt("raw", Ty::Bool, "Return the decoded bytes as application/octet-stream");
+const DISAMBIGUATION: Param = opt(
+    "disambiguation",
+    Ty::OneOf(&["compatible", "earlier", "later", "reject"]),
+    "For local times repeated or skipped by DST: compatible (default) takes the earlier of two and moves skipped times forward",
+);
+const EPOCH_UNIT: Param = opt("unit", Ty::OneOf(&["s", "ms", "us", "ns"]), "Unit of an integer at (default s)");
+const HASH_OUTPUT: Param = opt(
+    "output",
+    Ty::OneOf(&["hex", "HEX", "base64", "base64url"]),
+    "Digest encoding (default hex; HEX is uppercase)",
+);
+const HASH_ALGO: Param = p(
+    "algo",
+    Ty::OneOf(&[
+        "md5", "sha1", "sha224", "sha256", "sha384", "sha512", "sha512-256", "sha3-224", "sha3-256",
+        "sha3-384", "sha3-512", "blake2b", "blake2s", "blake3", "crc32", "xxh32", "xxh64", "xxh3",
+    ]),
+    "Hash algorithm",
+);
+const TEXT_PARAM: Param = p("text", Ty::MaxLen(MAX_TEXT), "Input text");
+const IMAGE_FORMAT: Param = opt(
+    "format",
+    Ty::OneOf(&["svg", "png", "txt"]),
+    "Image format (default svg); txt draws the code with Unicode blocks",
+);
+const QR_FORMAT: Param = opt(
+    "format",
+    Ty::OneOf(&["svg", "png", "txt", "json"]),
+    "Image format (default svg); json returns the encoded payload and symbol details",
+);
+const QR_ECC: Param = opt("ecc", Ty::OneOf(&["L", "M", "Q", "H"]), "Error correction level (default M; H survives ~30% damage)");
+const QR_VERSION: Param = opt("version", Ty::Range(1, 40), "Symbol version (default: smallest that fits)");
+const QR_MARGIN: Param = opt("margin", Ty::Range(0, MAX_QR_MARGIN as i64), "Quiet zone in modules (default 4)");
+const QR_FG: Param = opt("fg", Ty::Str, "Dark module color as 6-digit hex (default 000000)");
+const QR_BG: Param = opt("bg", Ty::Str, "Background color as 6-digit hex (default ffffff)");
+const IMAGE_SIZE: Param = opt("size", Ty::Range(16, MAX_IMAGE_PX as i64), "Exact width in pixels; not with scale");
+const IMAGE_SCALE: Param = opt("scale", Ty::Range(1, MAX_SCALE as i64), "Pixels per module (bar width for barcodes)");
+
+const ROUTE_DOCS: &[RouteDoc] = &[
+    // basic
+    route("index", "basic", "Interactive tool index (HTML)", Output::Html),
+    route("whoami", "basic", "Client IP, country, cookies and public headers", Output::Json("Whoami")),
+    route("ip", "basic", "Client IP address, resolved through trusted proxies", Output::Text),
+    route("ip_info", "basic", "Geolocation of the client IP from local MMDB files", Output::Json("IpInfo")),
+    route("ip_info_for", "basic", "Geolocation of an arbitrary IP address", Output::Json("IpInfo"))
+        .with(&[p("ip", Ty::Str, "IPv4 or IPv6 address")]),
+    route("headers", "basic", "Request headers, minus sensitive and proxy headers", Output::Json("Headers")),
+    route("user_agent", "basic", "The User-Agent header", Output::Text),
+    route("echo", "basic", "Echo the request back", Output::Json("Echo")),
+    route("echo_head", "basic", "Echo the request back (headers only)", Output::Json("Echo")),
+    route("echo_options", "basic", "Echo the request back", Output::Json("Echo")),
+    route("echo_post", "basic", "Echo the request and its body back", Output::Json("Echo")).body(Input::Any),
+    route("echo_put", "basic", "Echo the request and its body back", Output::Json("Echo")).body(Input::Any),
+    route("echo_patch", "basic", "Echo the request and its body back", Output::Json("Echo")).body(Input::Any),
+    route("echo_delete", "basic", "Echo the request and its body back", Output::Json("Echo")).body(Input::Any),
+    route("ping", "basic", "Liveness check; answers \"pong\"", Output::Text),
+    route("delay", "utils", "Answer \"OK\" after a delay", Output::Text)
+        .with(&[p("seconds", Ty::Range(0, 30), "Seconds to wait (values above 30 are clamped)")]),
+    route("status_code", "utils", "Respond with an empty body and the given status", Output::Empty)
+        .with(&[p("code", Ty::Range(100, 599), "HTTP status code")]),
+    // generators
+    route("generate_uuid", "generators", "Random UUID v4", Output::Text),
+    route("lorem", "generators", "Lorem ipsum text", Output::Text)
+        .with(&[p("words", Ty::Range(1, 1000), "Number of words (values above 1000 are clamped)")]),
+    route("random_color", "generators", "Random hex color like #1a2b3c", Output::Text),
+    route("generate_password", "generators", "Random password", Output::Text)
+        .with(&[p("length", Ty::Range(1, 128), "Password length (values above 128 are clamped)")]),
+    route("random_number", "generators", "Random integer in [min, max]", Output::Text).with(&[
+        p("min", Ty::Int, "Lower bound (inclusive)"),
+        p("max", Ty::Int, "Upper bound (inclusive); max - min is at most 1 billion"),
+    ]),
+    // encoding
+    route("base64_encode", "encoding", "Base64-encode text", Output::Text)
+        .with(&[p("text", Ty::MaxLen(1_000_000), "Text to encode")]),
+    route("base64_decode", "encoding", "Decode standard base64", Output::Text)
+        .with(&[p("b64", Ty::MaxLen(1_500_000), "Base64 input"), RAW]),
+    route("url_encode", "encoding", "Percent-encode text", Output::Text)
+        .with(&[p("text", Ty::MaxLen(100_000), "Text to encode")]),
+    route("url_decode", "encoding", "Decode percent-encoding", Output::Text)
+        .with(&[p("encoded", Ty::MaxLen(100_000), "Percent-encoded input"), RAW]),
+    route("hex_encode", "encoding", "Hex-encode text", Output::Text)
+        .with(&[p("text", Ty::MaxLen(MAX_ENC), "Text to encode")]),
+    route("hex_decode", "encoding", "Decode hex", Output::Text)
+        .with(&[p("input", Ty::MaxLen(2 * MAX_ENC), "Hex digits"), RAW]),
+    route("rot13", "encoding", "ROT13 text", Output::Text).with(&[p("text", Ty::MaxLen(MAX_ENC), "Input text")]),
+    route("html_encode", "encoding", "Escape HTML special characters", Output::Text)
+        .with(&[p("text", Ty::MaxLen(MAX_ENC), "Text to escape")]),
+    route("html_decode", "encoding", "Unescape HTML entities", Output::Text)
+        .with(&[p("text", Ty::MaxLen(MAX_ENC), "Text to unescape")]),
+    route("base64url_encode", "encoding", "Base64url-encode text (no padding)", Output::Text)
+        .with(&[p("text", Ty::MaxLen(1_000_000), "Text to encode")]),
+    route("base64url_decode", "encoding", "Decode base64url", Output::Text)
+        .with(&[p("input", Ty::MaxLen(1_500_000), "Base64url input"), RAW]),
+    route("base64_encode_body", "encoding", "Base64-encode the request body", Output::Text)
+        .body(Input::Body(1_000_000)),
+    route("base64_decode_body", "encoding", "Decode a base64 request body", Output::Text)
+        .with(&[RAW])
+        .body(Input::Body(1_500_000)),
+    route("url_encode_body", "encoding", "Percent-encode the request body", Output::Text).body(Input::Body(MAX_ENC)),
+    route("url_decode_body", "encoding", "Decode a percent-encoded request body", Output::Text)
+        .with(&[RAW])
+        .body(Input::Body(MAX_ENC)),
+    route("hex_encode_body", "encoding", "Hex-encode the request body", Output::Text).body(Input::Body(MAX_ENC)),
+    route("hex_decode_body", "encoding", "Decode a hex request body", Output::Text)
+        .with(&[RAW])
+        .body(Input::Body(2 * MAX_ENC)),
+    route("rot13_body", "encoding", "ROT13 the request body", Output::Text).body(Input::Body(MAX_ENC)),
+    route("html_encode_body", "encoding", "Escape HTML in the request body", Output::Text).body(Input::Body(MAX_ENC)),
+    route("html_decode_body", "encoding", "Unescape HTML entities in the request body", Output::Text)
+        .body(Input::Body(MAX_ENC)),
+    route("base64url_encode_body", "encoding", "Base64url-encode the request body", Output::Text)
+        .body(Input::Body(1_000_000)),
+    route("base64url_decode_body", "encoding", "Decode a base64url request body", Output::Text)
+        .with(&[RAW])
+        .body(Input::Body(1_500_000)),
+    // crypto
+    route("hash", "crypto", "Hash text", Output::Text).with(&[
+        HASH_ALGO,
+        p("text", Ty::MaxLen(MAX_HASH_INPUT), "Text to hash"),
+        HASH_OUTPUT,
+    ]),
+    route("hash_body", "crypto", "Hash the request body (e.g. a file)", Output::Text)
+        .with(&[HASH_ALGO, HASH_OUTPUT])
+        .body(Input::Body(MAX_HASH_BODY)),
+    route("hmac_compute", "crypto", "Compute or verify an HMAC", Output::Json("Hmac"))
+        .with(&[p("algo", Ty::OneOf(&["sha1", "sha256", "sha384", "sha512"]), "Hash function")])
+        .body(Input::Json("HmacRequest")),
+    route("jwt_decode", "crypto", "Decode a JWT without verifying it", Output::Json("JwtDecoded"))
+        .with(&[p("token", Ty::MaxLen(8192), "JWT in compact form")]),
+    route("jwt_verify", "crypto", "Verify a JWT signature with a secret or public key", Output::Json("JwtVerified"))
+        .body(Input::Json("JwtVerifyRequest")),
+    route("jwt_sign", "crypto", "Sign a JWT with a secret or private key", Output::Json("JwtSigned"))
+        .body(Input::Json("JwtSignRequest")),
+    // time
+    route("timestamp", "time", "Current unix time in seconds and milliseconds", Output::Json("Timestamp")),
+    route("timestamp_to_date", "time", "Unix timestamp (s or ms, auto-detected) as a date", Output::Json("TimestampDate"))
+        .with(&[p("unix", Ty::Int, "Unix timestamp in seconds or milliseconds")]),
+    route("time_utc", "time", "Current UTC date and time", Output::Json("Time")),
+    route("time_convert", "time", "Convert a time between IANA timezones", Output::Json("TimeConversion")).with(&[
+        opt("at", Ty::Str, "Anything /time/parse reads, local to from unless it has an offset (default now)"),
+        opt("from", Ty::Str, "IANA zone at is in (default UTC)"),
+        p("to", Ty::Str, "Comma-separated IANA zones to convert to (max 50)"),
+        DISAMBIGUATION,
+    ]),
+    route("time_parse", "time", "Parse a date in many formats, or a relative expression, to an instant", Output::Json("ParsedTime"))
+        .with(&[
+            p("at", Ty::Str, "RFC 3339 or 2822, 2026-W42-5, 2026-10-17 09:00, \"next friday 9am\", \"3 days ago\" or an epoch"),
+            opt("tz", Ty::Str, "IANA zone for times without an offset and for the local result (default UTC)"),
+            EPOCH_UNIT,
+            opt("now", Ty::Str, "Reference time for relative expressions (default the current time)"),
+            DISAMBIGUATION,
+        ]),
+    route("time_format", "time", "Format a date with a strftime pattern in a timezone", Output::Json("FormattedTime")).with(&[
+        p("pattern", Ty::MaxLen(MAX_PATTERN), "strftime pattern, e.g. %Y-%m-%d %H:%M %Z"),
+        opt("at", Ty::Str, "Anything /time/parse reads (default now)"),
+        opt("tz", Ty::Str, "IANA zone to format in (default UTC)"),
+        EPOCH_UNIT,
+        DISAMBIGUATION,
+    ]),
+    route("time_tz", "time", "Current time in an IANA timezone", Output::Json("Time"))
+        .with(&[p("tz", Ty::Str, "IANA zone name; may contain '/', e.g. America/New_York")]),
+    route("duration", "time", "Humanize a number of seconds", Output::Json("Duration"))
+        .with(&[p("seconds", Ty::Int, "Duration in seconds")]),
+    // text
+    route("slugify", "text", "URL slug from text", Output::Text).with(&[TEXT_PARAM]),
+    route("reverse", "text", "Reverse text", Output::Text).with(&[TEXT_PARAM]),
+    route("count", "text", "Count characters, bytes, words and lines", Output::Json("TextCount")).with(&[TEXT_PARAM]),
+    route("case", "text", "Change text case", Output::Text).with(&[
+        p("mode", Ty::OneOf(&["upper", "lower", "title", "snake", "kebab", "camel"]), "Target case"),
+        TEXT_PARAM,
+    ]),
+    // convert
+    route("base_convert", "convert", "Convert an integer between bases 2-36", Output::Json("BaseConvert")).with(&[
+        p("value", Ty::MaxLen(256), "Number written in the `from` base"),
+        p("from", Ty::Range(2, 36), "Source base"),
+        p("to", Ty::Range(2, 36), "Target base"),
+    ]),
+    route("hex_to_rgb", "convert", "Hex color to RGB", Output::Json("Color"))
+        .with(&[p("hex", Ty::Str, "6-digit hex color, with or without #")]),
+    route("rgb_to_hex", "convert", "RGB to hex color", Output::Json("Color")).with(&[
+        p("r", Ty::Range(0, 255), "Red"),
+        p("g", Ty::Range(0, 255), "Green"),
+        p("b", Ty::Range(0, 255), "Blue"),
+    ]),
+    // fun
+    route("cat_fact", "fun", "Random cat fact", Output::Text),
+    route("quote", "fun", "Random quote", Output::Text),
+    route("roll", "fun", "Roll dice in NdM notation", Output::Json("Roll"))
+        .with(&[p("dice", Ty::Str, "Dice like 2d6 (1-100 dice, 2-1000 sides)")]),
+    route("coinflip", "fun", "Flip a coin", Output::Json("Coinflip")),
+    route("eight_ball", "fun", "Magic 8-ball answer", Output::Json("EightBall")),
+    route("pick", "fun", "Pick one of several options", Output::Json("Pick"))
+        .with(&[p("options", Ty::MaxLen(10_000), "Comma-separated options")]),
+    // qr
+    route("qr", "qr", "QR code as SVG, PNG or text", Output::Image).with(&[
+        p("text", Ty::MaxLen(MAX_QR_TEXT), "Content to encode"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("wifi", "qr", "Wi-Fi network QR code (WIFI: payload)", Output::Image).with(&[
+        p("ssid", Ty::Str, "Network name"),
+        opt("password", Ty::Str, "Network password; required unless encryption is nopass"),
+        opt("encryption", Ty::OneOf(&["WPA", "SAE", "WEP", "nopass"]), "Security (default WPA with a password, else nopass)"),
+        opt("hidden", Ty::Bool, "Network doesn't broadcast its SSID"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("vcard", "qr", "Contact QR code (vCard 3.0)", Output::Image).with(&[
+        p("name", Ty::Str, "Full name; the last word is the family name"),
+        opt("org", Ty::Str, "Organization"),
+        opt("title", Ty::Str, "Job title"),
+        opt("phone", Ty::Str, "Phone number"),
+        opt("email", Ty::Str, "Email address"),
+        opt("url", Ty::Str, "Website"),
+        opt("address", Ty::Str, "Postal address on one line"),
+        opt("note", Ty::Str, "Free-form note"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("geo", "qr", "Location QR code (geo: URI)", Output::Image).with(&[
+        p("lat", Ty::Num, "Latitude, -90 to 90"),
+        p("lng", Ty::Num, "Longitude, -180 to 180"),
+        opt("alt", Ty::Num, "Altitude in meters"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("sms", "qr", "SMS QR code (SMSTO: payload)", Output::Image).with(&[
+        p("phone", Ty::Str, "Recipient number; spaces, dashes and parentheses are dropped"),
+        opt("message", Ty::Str, "Prefilled message"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("email", "qr", "Email QR code (mailto: URI)", Output::Image).with(&[
+        p("to", Ty::Str, "Recipient address(es), comma-separated"),
+        opt("cc", Ty::Str, "Cc address(es), comma-separated"),
+        opt("subject", Ty::Str, "Prefilled subject"),
+        opt("body", Ty::Str, "Prefilled body"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("event", "qr", "Calendar event QR code (iCalendar VEVENT)", Output::Image).with(&[
+        p("summary", Ty::Str, "Event title"),
+        p("start", Ty::Str, "2026-10-17 (all day), 2026-10-17T09:00 (local) or RFC 3339 with an offset"),
+        opt("end", Ty::Str, "End, in the same form as start"),
+        opt("location", Ty::Str, "Where"),
+        opt("description", Ty::Str, "Details"),
+        QR_FORMAT, IMAGE_SIZE, IMAGE_SCALE, QR_ECC, QR_VERSION, QR_MARGIN, QR_FG, QR_BG,
+    ]),
+    route("barcode", "qr", "1D barcode with human-readable text as SVG, PNG or text", Output::Image).with(&[
+        p("text", Ty::MaxLen(MAX_BARCODE_TEXT), "Content to encode; (AI)value pairs for gs1-128"),
+        opt(
+            "symbology",
+            Ty::OneOf(&["code128", "gs1-128", "code39", "code93", "codabar", "ean13", "ean8", "upca", "itf14", "itf"]),
+            "Barcode type (default code128); check digits are added when left off",
+        ),
+        IMAGE_FORMAT,
+        IMAGE_SIZE,
+        IMAGE_SCALE,
+        opt("height", Ty::Range(10, MAX_BAR_HEIGHT as i64), "Bar height in pixels (default 120)"),
+        opt("margin", Ty::Range(0, MAX_QR_MARGIN as i64), "Quiet zone in modules each side (default 10)"),
+        opt("show_text", Ty::Bool, "Print the human-readable text under the bars (default true)"),
+        QR_FG,
+        QR_BG,
+    ]),
+    route("aztec_code", "qr", "Aztec Code as SVG, PNG or text; X-Symbol-Size has its rows x columns", Output::Image).with(&[
+        p("text", Ty::MaxLen(MAX_AZTEC_TEXT), "Content to encode"),
+        opt(
+            "ecc",
+            Ty::Range(aztec::MIN_ECC_PERCENT as i64, aztec::MAX_ECC_PERCENT as i64),
+            "Share of the symbol given to error correction, in percent (default 23)",
+        ),
+        opt("layers", Ty::Range(1, aztec::MAX_LAYERS as i64), "Force the number of data layers (1-4 when compact)"),
+        opt("compact", Ty::Bool, "Compact (1-4 layers) or full-range symbol (default: whichever is smaller)"),
+        IMAGE_FORMAT,
+        IMAGE_SIZE,
+        IMAGE_SCALE,
+        opt("margin", Ty::Range(0, MAX_QR_MARGIN as i64), "Quiet zone in modules (default 2)"),
+        QR_FG,
+        QR_BG,
+    ]),
+    route("pdf417_code", "qr", "PDF417 as SVG, PNG or text; X-Symbol-Size has its codeword rows x data columns", Output::Image)
+        .with(&[
+            p("text", Ty::MaxLen(MAX_PDF417_TEXT), "Content to encode"),
+            opt(
+                "ecc",
+                Ty::Range(0, pdf417::MAX_ECC_LEVEL as i64),
+                "Error correction level, 2^(level+1) check words (default: the spec's recommendation for the data size)",
+            ),
+            opt("columns", Ty::Range(1, pdf417::MAX_COLUMNS as i64), "Data columns (default: about three times as wide as tall)"),
+            IMAGE_FORMAT,
+            IMAGE_SIZE,
+            IMAGE_SCALE,
+            opt("margin", Ty::Range(0, MAX_QR_MARGIN as i64), "Quiet zone in modules (default 2)"),
+            QR_FG,
+            QR_BG,
+        ]),
+    route("labels", "qr", "Sheet of labels, one code each, as an SVG page or a multi-page PDF", Output::Document)
+        .with(&[opt("format", Ty::OneOf(&["svg", "pdf"]), "Output format (default svg; SVG holds one page)")])
+        .body(Input::Json("LabelsRequest")),
+    route("qr_decode", "qr", "Read a QR code from an uploaded image", Output::Json("Decoded")).body(Input::Image),
+    route("barcode_decode", "qr", "Read a 1D barcode from an uploaded image", Output::Json("Decoded"))
+        .with(&[opt(
+            "symbology",
+            Ty::OneOf(&["code128", "gs1-128", "code39", "code93", "codabar", "ean13", "ean8", "upca", "itf14", "itf"]),
+            "Only look for this barcode type (default: any)",
+        )])
+        .body(Input::Image),
+    route("data_matrix", "qr", "Data Matrix (ECC 200) as SVG, PNG or text; X-Symbol-Size has its rows x columns", Output::Image)
+        .with(&[
+            p("text", Ty::MaxLen(MAX_DM_TEXT), "Content to encode; (AI)value pairs with gs1"),
+            opt("shape", Ty::OneOf(&["auto", "square", "rectangle"]), "Symbol shapes to pick from (default auto)"),
+            opt("symbol", Ty::Str, "Force a symbol size: 16 or 16x48, rows x columns (DMRE sizes allowed); not with shape"),
+            opt("gs1", Ty::Bool, "GS1 DataMatrix: FNC1 first, AIs validated, GS between variable-length elements"),
+            IMAGE_FORMAT,
+            IMAGE_SIZE,
+            IMAGE_SCALE,
+            opt("margin", Ty::Range(0, MAX_QR_MARGIN as i64), "Quiet zone in modules (default 1)"),
+            QR_FG,
+            QR_BG,
+        ]),
+    // docs
+    route("openapi_json", "docs", "This OpenAPI document", Output::Json("OpenApi")),
+    route("docs", "docs", "Browsable API reference (HTML)", Output::Html),
+];
+
+// Tags are the endpoint modules, which double as API key scopes (see auth.rs).
+const TAGS: &[(&str, &str)] = &[
+    ("basic", "Client IP, geolocation, headers, echo and ping"),
+    ("encoding", "Base64, URL, hex, HTML and ROT13; GET takes the input in the URL, POST in the body"),
+    ("crypto", "Hashes, HMAC and JWT"),
+    ("generators", "UUIDs, passwords, lorem ipsum and random values"),
+    ("time", "Timestamps, timezones and durations"),
+    ("text", "Text transforms"),
+    ("convert", "Number bases and colors"),
+    ("qr", "QR codes, barcodes, Data Matrix, Aztec and PDF417"),
+    ("fun", "Dice, coins and quotes"),
+    ("utils", "Delays and status codes for HTTP testing"),
+    ("docs", "Documentation"),
+];
+
+/// `secured`: API keys are enforced, so non-public operations declare a security requirement.
+fn build<'a>(routes: impl Iterator<Item = &'a Route>, secured: bool) -> Value {
+    let mut paths: BTreeMap<String, Map<String, Value>> = BTreeMap::new();
+    for route in routes {
+        let name = route.name.as_deref().unwrap_or_default();
+        let doc = ROUTE_DOCS.iter().find(|d| d.name == name);
+
+        let mut params = Vec::new();
+        let mut path = String::new();
+        for segment in route.uri.path().split('/').filter(|s| !s.is_empty()) {
+            path.push('/');
+            match dynamic(segment) {
+                Some(param) => {
+                    path.push_str(&format!("{{{}}}", param));
+                    params.push(parameter(param, "path", doc));
+                }
+                None => path.push_str(segment),
+            }
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+        for field in route.uri.query().unwrap_or_default().split('&') {
+            let Some(param) = dynamic(field) else { continue };
+            if field.ends_with("..>") {
+                // A form struct: its fields are the documented params not named in the URI.
+                let named: Vec<Value> = params.iter().map(|p| p["name"].clone()).collect();
+                for extra in doc.map_or(&[][..], |d| d.params) {
+                    if !named.contains(&json!(extra.name)) {
+                        params.push(parameter(extra.name, "query", doc));
+                    }
+                }
+            } else {
+                params.push(parameter(param, "query", doc));
+            }
+        }
+
+        let mut op = operation(name, doc, params);
+        if secured && !PUBLIC_ROUTES.contains(&name) {
+            op["security"] = json!([{"bearer": []}, {"apiKey": []}]);
+        }
+        paths.entry(path).or_default().insert(route.method.as_str().to_lowercase(), op);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Tools API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Developer utilities over HTTP. Text and JSON endpoints honour `?format=text|json|yaml` \
+                and the Accept header; errors always use the `Error` shape with a real HTTP status.",
+        },
+        "tags": TAGS.iter().map(|(name, description)| json!({"name": name, "description": description})).collect::<Vec<_>>(),
+        "paths": paths,
+        "components": components(),
+    })
+}
+
+/// `<name>` or `<name..>` -> `name`; static segments -> None.
+fn dynamic(segment: &str) -> Option<&str> {
+    segment
+        .strip_prefix('<')
+        .and_then(|s| s.strip_suffix('>'))
+        .map(|s| s.trim_end_matches(".."))
+}
+
+fn parameter(name: &str, location: &str, doc: Option<&RouteDoc>) -> Value {
+    let param = doc.and_then(|d| d.params.iter().find(|p| p.name == name));
+    let schema = match param.map_or(Ty::Str, |p| p.ty) {
+        Ty::Str => json!({"type": "string"}),
+        Ty::MaxLen(n) => json!({"type": "string", "maxLength": n}),
+        Ty::Int => json!({"type": "integer", "format": "int64"}),
+        Ty::Num => json!({"type": "number"}),
+        Ty::Range(min, max) => json!({"type": "integer", "minimum": min, "maximum": max}),
+        Ty::Bool => json!({"type": "boolean"}),
+        Ty::OneOf(values) => json!({"type": "string", "enum": values}),
+    };
+    json!({
+        "name": name,
+        "in": location,
+        "required": location == "path" || param.is_some_and(|p| p.required),
+        "description": param.map_or("", |p| p.doc),
+        "schema": schema,
+    })
+}
+
+fn operation(name: &str, doc: Option<&RouteDoc>, mut params: Vec<Value>) -> Value {
+    let mut op = Map::new();
+    op.insert("operationId".into(), json!(name));
+    let Some(doc) = doc else {
+        op.insert("responses".into(), json!({"default": {"$ref": "#/components/responses/Error"}}));
+        return Value::Object(op);
+    };
+    op.insert("tags".into(), json!([doc.tag]));
+    op.insert("summary".into(), json!(doc.summary));
+
+    let raw = doc.params.iter().any(|p| p.name == "raw");
+    let ok = match doc.output {
+        Output::Text => {
+            params.push(json!({"$ref": "#/components/parameters/format"}));
+            let mut content = json!({
+                "text/plain": {"schema": {"type": "string"}},
+                "application/json": {"schema": {"$ref": "#/components/schemas/Result"}},
+                "application/yaml": {"schema": {"$ref": "#/components/schemas/Result"}},
+            });
+            if raw {
+                content["application/octet-stream"] = json!({"schema": {"type": "string", "format": "binary"}});
+            }
+            json!({"description": "Result", "content": content})
+        }
+        Output::Json(schema) => {
+            params.push(json!({"$ref": "#/components/parameters/format"}));
+            let schema = json!({"$ref": format!("#/components/schemas/{}", schema)});
+            json!({"description": "Result", "content": {
+                "application/json": {"schema": schema},
+                "application/yaml": {"schema": schema},
+                "text/plain": {"schema": {"type": "string", "description": "`key: value` lines"}},
+            }})
+        }
+        Output::Image => json!({"description": "Image", "content": {
+            "image/svg+xml": {"schema": {"type": "string"}},
+            "image/png": {"schema": {"type": "string", "format": "binary"}},
+            "text/plain": {"schema": {"type": "string"}},
+        }}),
+        Output::Document => json!({"description": "Printable page(s)", "content": {
+            "image/svg+xml": {"schema": {"type": "string"}},
+            "application/pdf": {"schema": {"type": "string", "format": "binary"}},
+        }}),
+        Output::Html => json!({"description": "HTML page", "content": {"text/html": {"schema": {"type": "string"}}}}),
+        Output::Empty => json!({"description": "Empty body with the requested status"}),
+    };
+    let mut responses = json!({
+        "4XX": {"$ref": "#/components/responses/Error"},
+        "5XX": {"$ref": "#/components/responses/Error"},
+    });
+    match doc.output {
+        Output::Empty => responses["default"] = ok,
+        _ => responses["200"] = ok,
+    }
+
+    match doc.input {
+        Input::None => {}
+        Input::Body(max) => {
+            let text = json!({"type": "string", "maxLength": max});
+            op.insert("requestBody".into(), json!({
+                "required": true,
+                "description": format!("Raw body, or {{\"input\": ...}} JSON; at most {} bytes", max),
+                "content": {
+                    "application/octet-stream": {"schema": {"type": "string", "format": "binary", "maxLength": max}},
+                    "text/plain": {"schema": text},
+                    "application/json": {"schema": {"$ref": "#/components/schemas/Input"}},
+                },
+            }));
+        }
+        Input::Json(schema) => {
+            op.insert("requestBody".into(), json!({
+                "required": true,
+                "content": {"application/json": {"schema": {"$ref": format!("#/components/schemas/{}", schema)}}},
+            }));
+        }
+        Input::Any => {
+            op.insert("requestBody".into(), json!({
+                "required": false,
+                "description": "Any payload, capped by the `bytes` limit; non-UTF-8 bodies are echoed as base64",
+                "content": {"*/*": {"schema": {"type": "string", "format": "binary"}}},
+            }));
+        }
+        Input::Image => {
+            let binary = json!({"type": "string", "format": "binary"});
+            op.insert("requestBody".into(), json!({
+                "required": true,
+                "description": "A PNG or JPEG of at most 4 megapixels, capped by the `bytes` limit; or a multipart form with an `image` file field, capped by `data-form`",
+                "content": {
+                    "image/png": {"schema": binary},
+                    "image/jpeg": {"schema": binary},
+                    "multipart/form-data": {"schema": {"type": "object", "required": ["image"], "properties": {"image": binary}}},
+                },
+            }));
+        }
+    }
+
+    op.insert("parameters".into(), Value::Array(params));
+    op.insert("responses".into(), responses);
+    Value::Object(op)
+}
+
+fn components() -> Value {
+    let string = json!({"type": "string"});
+    let nullable = json!({"type": "string", "nullable": true});
+    let int = json!({"type": "integer", "format": "int64"});
+    let strings = json!({"type": "object", "additionalProperties": {"type": "string"}});
+    let claim = json!({"type": "object", "properties": {
+        "value": int, "utc": nullable, "relative": string,
+        "status": {"type": "string", "enum": ["ok", "expired", "not_yet_valid", "issued_in_future"]},
+    }});
+    let claims = json!({"type": "object", "properties": {"exp": claim, "nbf": claim, "iat": claim}});
+    let object = json!({"type": "object", "additionalProperties": true});
+    json!({
+        "securitySchemes": {
+            "bearer": {"type": "http", "scheme": "bearer"},
+            "apiKey": {"type": "apiKey", "in": "header", "name": "X-API-Key"},
+        },
+        "parameters": {
+            "format": {
+                "name": "format",
+                "in": "query",
+                "required": false,
+                "description": "Response format; overrides the Accept header",
+                "schema": {"type": "string", "enum": ["text", "json", "yaml"]},
+            },
+        },
+        "responses": {
+            "Error": {
+                "description": "Error",
+                "content": {
+                    "application/json": {"schema": {"$ref": "#/components/schemas/Error"}},
+                    "application/yaml": {"schema": {"$ref": "#/components/schemas/Error"}},
+                    "text/plain": {"schema": {"type": "string", "description": "Error: <message>"}},
+                },
+            },
+        },
+        "schemas": {
+            "Error": {"type": "object", "required": ["error", "code", "status"], "properties": {
+                "error": {"type": "string", "description": "Human-readable message"},
+                "code": {"type": "string", "enum": [
+                    "bad_request", "not_found", "input_too_large", "invalid_input",
+                    "unsupported_option", "not_configured", "rate_limited", "unauthorized", "forbidden",
+                    "quota_exceeded", "internal_error",
+                ]},
+                "status": {"type": "integer"},
+                "ip": {"type": "string", "description": "The address looked up, on /ip-info errors"},
+            }},
+            "Result": {"type": "object", "required": ["result"], "properties": {"result": string}},
+            "Input": {"type": "object", "required": ["input"], "properties": {"input": string}},
+            "Headers": strings,
+            "Whoami": {"type": "object", "properties": {
+                "ip": string, "country": nullable, "cookies": strings, "headers": strings,
+            }},
+            "IpInfo": {"type": "object", "properties": {
+                "ip": string, "city": nullable, "region": nullable, "country": nullable,
+                "asn": nullable, "org": nullable,
+            }},
+            "Echo": {"type": "object", "properties": {
+                "method": string, "query": strings, "headers": strings, "content_type": nullable,
+                "content_length": {"type": "integer", "nullable": true}, "body": string,
+                "body_encoding": {"type": "string", "enum": ["utf8", "base64"]},
+            }},
+            "Timestamp": {"type": "object", "properties": {"seconds": int, "milliseconds": int}},
+            "TimestampDate": {"type": "object", "properties": {
+                "unix": int, "interpreted_as": {"type": "string", "enum": ["seconds", "milliseconds"]},
+                "utc": string, "iso8601": string,
+            }},
+            "Time": {"type": "object", "properties": {"datetime": string, "timezone": string}},
+            "ZonedTime": {"type": "object", "properties": {
+                "timezone": string, "datetime": string, "utc_offset": string, "offset_seconds": int,
+                "abbreviation": string, "dst": {"type": "boolean"},
+            }},
+            "TimeConversion": {"type": "object", "properties": {
+                "utc": string, "unix": int,
+                "local_time": {"type": "string", "enum": ["unique", "ambiguous", "nonexistent"]},
+                "note": {"type": "string", "description": "How an ambiguous or nonexistent local time was resolved"},
+                "from": {"$ref": "#/components/schemas/ZonedTime"},
+                "to": {"type": "array", "items": {"$ref": "#/components/schemas/ZonedTime"}},
+            }},
+            "ParsedTime": {"type": "object", "properties": {
+                "input": string,
+                "format": {"type": "string", "enum": ["rfc3339", "rfc2822", "iso_week", "datetime", "epoch", "relative"]},
+                "utc": string, "local": {"$ref": "#/components/schemas/ZonedTime"},
+                "local_time": {"type": "string", "enum": ["unique", "ambiguous", "nonexistent"]},
+                "note": string, "epoch": {"$ref": "#/components/schemas/Epoch"},
+            }},
+            "FormattedTime": {"type": "object", "properties": {
+                "formatted": string, "pattern": string, "timezone": string, "utc": string,
+                "epoch": {"$ref": "#/components/schemas/Epoch"},
+            }},
+            "Epoch": {"type": "object", "properties": {
+                "seconds": int, "milliseconds": int, "microseconds": int,
+                "nanoseconds": {"type": "integer", "format": "int64", "nullable": true, "description": "null outside 1677-2262"},
+            }},
+            "Duration": {"type": "object", "properties": {
+                "seconds": int, "human": string, "days": int, "hours": int, "minutes": int,
+            }},
+            "TextCount": {"type": "object", "properties": {
+                "characters": int, "bytes": int, "words": int, "lines": int,
+            }},
+            "BaseConvert": {"type": "object", "properties": {
+                "input": string, "from_base": int, "to_base": int,
+                "decimal": {"type": "string", "description": "Decimal value (as a string: up to 128 bits)"},
+                "result": string,
+            }},
+            "Color": {"type": "object", "properties": {
+                "hex": string, "r": int, "g": int, "b": int, "rgb": string,
+            }},
+            "Roll": {"type": "object", "properties": {
+                "dice": string, "rolls": {"type": "array", "items": int}, "total": int,
+            }},
+            "Coinflip": {"type": "object", "properties": {"result": {"type": "string", "enum": ["heads", "tails"]}}},
+            "EightBall": {"type": "object", "properties": {"answer": string}},
+            "Pick": {"type": "object", "properties": {
+                "options": {"type": "array", "items": string}, "chosen": string,
+            }},
+            "LabelsRequest": {"type": "object", "required": ["items"], "properties": {
+                "items": {"type": "array", "minItems": 1, "maxItems": MAX_LABELS, "items": {
+                    "type": "object", "required": ["text"], "properties": {
+                        "text": {"type": "string", "maxLength": MAX_LABEL_TEXT},
+                        "symbology": {"type": "string", "description": "qr (default), datamatrix, aztec or a /barcode symbology"},
+                        "caption": {"type": "string", "description": "Printed under the code; 1D codes default to their digits"},
+                    },
+                }},
+                "layout": {"type": "object", "properties": {
+                    "page": {"type": "string", "enum": ["a4", "letter"]},
+                    "rows": {"type": "integer", "minimum": 1, "maximum": MAX_LABEL_GRID, "description": "Default 8"},
+                    "cols": {"type": "integer", "minimum": 1, "maximum": MAX_LABEL_GRID, "description": "Default 3"},
+                    "margin": {"type": "number", "minimum": 0, "maximum": 50, "description": "Page margin in mm (default 10)"},
+                    "gap": {"type": "number", "minimum": 0, "maximum": 20, "description": "Space between labels in mm (default 2)"},
+                }},
+            }},
+            "Decoded": {"type": "object", "properties": {
+                "text": string,
+                "symbology": {"type": "string", "enum": [
+                    "qr", "code128", "gs1-128", "code39", "code93", "codabar", "ean13", "ean8", "upca", "itf14", "itf",
+                ]},
+                "bounds": {"type": "object", "description": "Axis-aligned box around the symbol, in pixels", "properties": {
+                    "x": {"type": "number"}, "y": {"type": "number"}, "width": {"type": "number"}, "height": {"type": "number"},
+                }},
+                "corners": {
+                    "type": "array",
+                    "description": "[x, y] corners clockwise from the symbol's top-left as read",
+                    "items": {"type": "array", "items": {"type": "number"}, "minItems": 2, "maxItems": 2},
+                },
+                "version": {"type": "integer", "description": "QR only"},
+                "ecc": {"type": "string", "enum": ["L", "M", "Q", "H"], "description": "QR only"},
+            }},
+            "HmacRequest": {"type": "object", "required": ["key", "message"], "properties": {
+                "key": {"type": "string", "maxLength": MAX_HASH_BODY},
+                "message": {"type": "string", "maxLength": MAX_HASH_BODY},
+                "key_encoding": {"type": "string", "enum": ["utf8", "hex", "base64"]},
+                "signature": {"type": "string", "description": "Verify against this MAC (hex, sha256=<hex> or base64)"},
+            }},
+            "Hmac": {"type": "object", "properties": {
+                "algorithm": string, "hex": string, "base64": string,
+                "valid": {"type": "boolean", "description": "Only when a signature was supplied"},
+            }},
+            "JwtDecoded": {"type": "object", "properties": {
+                "warning": string, "algorithm": string, "expired": {"type": "boolean"},
+                "not_yet_valid": {"type": "boolean"}, "claims": claims, "header": object,
+                "payload": object, "alg_warning": string,
+            }},
+            "JwtVerifyRequest": {"type": "object", "required": ["token"], "properties": {
+                "token": {"type": "string", "maxLength": 8192},
+                "secret": {"type": "string", "description": "HS256/384/512 secret"},
+                "key": {"description": "Public key as PEM, or a JWK (object or string)"},
+            }},
+            "JwtVerified": {"type": "object", "properties": {
+                "algorithm": string, "signature_valid": {"type": "boolean"}, "header": object,
+                "claims": claims, "payload": object,
+            }},
+            "JwtSignRequest": {"type": "object", "required": ["payload"], "properties": {
+                "header": object, "payload": object,
+                "secret": {"type": "string", "description": "HS256/384/512 secret"},
+                "key": {"type": "string", "description": "PKCS#8 private key PEM"},
+            }},
+            "JwtSigned": {"type": "object", "properties": {"algorithm": string, "token": string}},
+            "OpenApi": object,
+        },
+    })
+}
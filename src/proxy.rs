@@ -0,0 +1,113 @@
+use std::net::{IpAddr, SocketAddr};
+
+use ipnet::IpNet;
+use rocket::fairing::AdHoc;
+use rocket::serde::Deserialize;
+use rocket::Request;
+
+// Trusted-proxy model for resolving the real client IP, configured under
+// `[default.proxy]` in Rocket.toml. Forwarding headers are only honoured when the
+// TCP peer is a trusted proxy, so direct clients can't spoof `/ip` with them.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// CIDRs of proxies/load balancers allowed to set forwarding headers.
+    pub trusted_proxies: Vec<IpNet>,
+    /// Headers to consult, in priority order: x-real-ip, x-forwarded-for,
+    /// forwarded (RFC 7239) and cf-connecting-ip.
+    pub trusted_headers: Vec<String>,
+}
+
+impl Default for ProxyConfig {
+    // Matches the stock deployment: nginx on the same host, binding to localhost.
+    fn default() -> Self {
+        ProxyConfig {
+            trusted_proxies: vec!["127.0.0.0/8".parse().unwrap(), "::1/128".parse().unwrap()],
+            trusted_headers: vec!["x-real-ip".to_string(), "x-forwarded-for".to_string()],
+        }
+    }
+}
+
+impl ProxyConfig {
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Trusted proxies", |rocket| async {
+            match rocket.figment().extract_inner::<ProxyConfig>("proxy") {
+                Ok(config) => Ok(rocket.manage(config)),
+                Err(e) if e.missing() => Ok(rocket.manage(ProxyConfig::default())),
+                Err(e) => {
+                    error!("invalid [proxy] config: {}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Resolve the client address for `request`. Falls back to the peer address,
+    /// then to 127.0.0.1 when Rocket has no peer (e.g. local test clients).
+    pub fn client_ip(&self, request: &Request<'_>) -> IpAddr {
+        let peer = match request.remote() {
+            Some(addr) => addr.ip(),
+            None => return IpAddr::from([127, 0, 0, 1]),
+        };
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        self.trusted_headers
+            .iter()
+            .find_map(|name| {
+                let values: Vec<&str> = request.headers().get(name).collect();
+                if values.is_empty() {
+                    return None;
+                }
+                match name.to_lowercase().as_str() {
+                    "x-forwarded-for" => self.walk_chain(values.iter().flat_map(|v| v.split(',')).map(parse_addr)),
+                    "forwarded" => self.walk_chain(values.iter().flat_map(|v| v.split(',')).map(forwarded_for)),
+                    // Single-address headers set by the proxy itself
+                    _ => values.last().and_then(|v| parse_addr(v)),
+                }
+            })
+            .unwrap_or(peer)
+    }
+
+    /// Walk a hop list right-to-left, skipping trusted proxies, and return the first
+    /// untrusted hop. An unparseable hop ends the walk: nothing left of it can be trusted.
+    fn walk_chain<I>(&self, hops: I) -> Option<IpAddr>
+    where
+        I: DoubleEndedIterator<Item = Option<IpAddr>>,
+    {
+        let mut leftmost = None;
+        for hop in hops.rev() {
+            let ip = hop?;
+            if !self.is_trusted(ip) {
+                return Some(ip);
+            }
+            leftmost = Some(ip);
+        }
+        // Every hop was a trusted proxy; the origin is the leftmost one.
+        leftmost
+    }
+}
+
+fn parse_addr(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+    value
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| value.trim_start_matches('[').trim_end_matches(']').parse().ok())
+}
+
+/// Extract the `for=` node from one RFC 7239 element, e.g.
+/// `for="[2001:db8::17]:4711";proto=https`.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    element
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+        .and_then(|(_, value)| parse_addr(value))
+}
@@ -0,0 +1,327 @@
+// Module grids for the code generators, drawn as SVG, PNG or terminal text. PNGs are
+// two-color palette images at 1 bit per pixel. Captions (a barcode's human-readable line) use a built-in 5x7 pixel font in PNG and
+// text output, and a monospace <text> element in SVG.
+
+/// Pixel geometry for drawing a grid: module size, and a canvas at least as large as
+/// the grid with the grid centred (extra room widens the quiet zone). A caption band,
+/// if any, takes the bottom `caption` pixels of the canvas.
+#[derive(Clone, Copy)]
+pub struct Layout {
+    pub module_w: usize,
+    pub module_h: usize,
+    pub width: usize,
+    pub height: usize,
+    pub caption: usize,
+}
+
+impl Layout {
+    /// Canvas exactly the size of `modules`.
+    pub fn fit(modules: &Modules, module_w: usize, module_h: usize) -> Layout {
+        Layout { module_w, module_h, width: modules.width * module_w, height: modules.height * module_h, caption: 0 }
+    }
+
+    /// Adds a band under the grid for a caption of `chars` characters. Font pixels match
+    /// the module width, shrinking (down to 1px) if the line wouldn't fit the canvas.
+    pub fn with_caption(mut self, chars: usize) -> Layout {
+        let font_px = self.module_w.min(self.width / (GLYPH_ADVANCE * chars).max(1)).max(1);
+        self.caption = CAPTION_ROWS * font_px;
+        self.height += self.caption;
+        self
+    }
+
+    fn font_px(&self) -> usize {
+        (self.caption / CAPTION_ROWS).max(1)
+    }
+
+    /// Top-left pixel of the grid.
+    fn origin(&self, modules: &Modules) -> (usize, usize) {
+        (
+            self.width.saturating_sub(modules.width * self.module_w) / 2,
+            (self.height - self.caption).saturating_sub(modules.height * self.module_h) / 2,
+        )
+    }
+
+    /// Left pixel of a centred caption line; negative when the line is clipped.
+    fn caption_x(&self, chars: usize) -> isize {
+        (self.width as isize - (GLYPH_ADVANCE * chars * self.font_px()) as isize) / 2
+    }
+}
+
+/// Glyph cell: 5 columns plus 1 of spacing; 7 rows plus a row of padding above and below.
+pub const GLYPH_ADVANCE: usize = 6;
+const CAPTION_ROWS: usize = 9;
+
+/// Colors as `#rrggbb`.
+pub fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// A grid of dark/light modules (QR/Data Matrix cells, barcode bars).
+pub struct Modules {
+    width: usize,
+    height: usize,
+    dark: Vec<bool>,
+}
+
+impl Modules {
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> bool) -> Modules {
+        let dark = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| f(x, y)).collect();
+        Modules { width, height, dark }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.dark[y * self.width + x]
+    }
+
+    /// Copy surrounded by `x` light modules left and right and `y` above and below.
+    pub fn with_quiet_zone(&self, x: usize, y: usize) -> Modules {
+        Modules::from_fn(self.width + 2 * x, self.height + 2 * y, |px, py| {
+            px >= x && py >= y && self.is_dark(px - x, py - y)
+        })
+    }
+
+    /// Two module rows per line with half blocks, dark modules drawn as ink; the caption
+    /// goes on its own line, centred.
+    pub fn to_text(&self, caption: Option<&str>) -> String {
+        let mut out = String::with_capacity((self.width * 3 + 1) * self.height.div_ceil(2));
+        for y in (0..self.height).step_by(2) {
+            for x in 0..self.width {
+                out.push(match (self.is_dark(x, y), self.is_dark(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            out.push('\n');
+        }
+        if let Some(caption) = caption {
+            let pad = self.width.saturating_sub(caption.chars().count()) / 2;
+            out.push_str(&format!("{}{}\n", " ".repeat(pad), caption));
+        }
+        out
+    }
+
+    /// SVG with one rect per horizontal run of dark modules.
+    pub fn to_svg(&self, layout: Layout, fg: [u8; 3], bg: [u8; 3], caption: Option<&str>) -> String {
+        let (ox, oy) = layout.origin(self);
+        let mut rects = String::new();
+        for y in 0..self.height {
+            let mut x = 0;
+            while x < self.width {
+                if !self.is_dark(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let run = (x..self.width).take_while(|&i| self.is_dark(i, y)).count();
+                rects.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                    ox + x * layout.module_w,
+                    oy + y * layout.module_h,
+                    run * layout.module_w,
+                    layout.module_h
+                ));
+                x += run;
+            }
+        }
+        if let Some(caption) = caption.filter(|_| layout.caption > 0) {
+            // 7px caps in a 10px monospace font match the raster glyphs closely enough.
+            let font_px = layout.font_px();
+            rects.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\">{}</text>",
+                layout.width / 2,
+                layout.height - 2 * font_px,
+                10 * font_px,
+                xml_escape(caption)
+            ));
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\"><rect width=\"100%\" height=\"100%\" fill=\"{}\"/><g fill=\"{}\">{}</g></svg>",
+            layout.width,
+            layout.height,
+            hex(bg),
+            hex(fg),
+            rects
+        )
+    }
+
+    /// PNG of the grid on `layout`'s canvas, `fg` for dark modules.
+    pub fn to_png(&self, layout: Layout, fg: [u8; 3], bg: [u8; 3], caption: Option<&str>) -> Vec<u8> {
+        let (w, h) = (layout.width, layout.height);
+        let (ox, oy) = layout.origin(self);
+        let stride = w.div_ceil(8);
+        let mut raw = Vec::with_capacity(stride * h);
+        for y in 0..h {
+            let mut row = vec![0u8; stride];
+            if let Some(my) = y.checked_sub(oy).map(|py| py / layout.module_h).filter(|&my| my < self.height) {
+                for x in 0..w {
+                    let dark = x
+                        .checked_sub(ox)
+                        .map(|px| px / layout.module_w)
+                        .is_some_and(|mx| self.is_dark(mx, my));
+                    if dark {
+                        row[x / 8] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+            if let Some(caption) = caption.filter(|_| y >= h - layout.caption) {
+                draw_caption_row(&mut row, &layout, caption, y - (h - layout.caption));
+            }
+            raw.extend_from_slice(&row);
+        }
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, w as u32, h as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::One);
+        encoder.set_palette(bg.iter().chain(fg.iter()).copied().collect::<Vec<u8>>());
+        encoder.set_compression(png::Compression::Best);
+        // Only fails on a zero-sized canvas or a short buffer, neither of which `Layout` allows.
+        let mut writer = encoder.write_header().expect("valid PNG header");
+        writer.write_image_data(&raw).expect("image data matches the header");
+        writer.finish().expect("PNG written to memory");
+        png
+    }
+}
+
+/// Sets the ink pixels of caption pixel row `y` (counted from the top of the band).
+fn draw_caption_row(row: &mut [u8], layout: &Layout, caption: &str, y: usize) {
+    let font_px = layout.font_px();
+    let Some(glyph_row) = (y / font_px).checked_sub(1).filter(|&r| r < 7) else { return };
+    let left = layout.caption_x(caption.chars().count());
+    for (i, c) in caption.chars().enumerate() {
+        let columns = glyph(c);
+        for (col, bits) in columns.iter().enumerate() {
+            if bits >> glyph_row & 1 == 0 {
+                continue;
+            }
+            let x0 = left + ((i * GLYPH_ADVANCE + col) * font_px) as isize;
+            for x in x0..x0 + font_px as isize {
+                if (0..layout.width as isize).contains(&x) {
+                    let x = x as usize;
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+    }
+}
+
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// 5x7 glyphs for printable ASCII, one byte per column, least significant bit at the top.
+/// Anything else is drawn as '?'.
+fn glyph(c: char) -> [u8; 5] {
+    let i = match c {
+        ' '..='~' => c as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    FONT[i]
+}
+
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use rocket::fairing::AdHoc;
+use rocket::http::ext::IntoOwned;
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Method, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::serde::Deserialize;
+use rocket::State;
+use sha2::{Digest, Sha256};
+
+use crate::auth::ApiKeys;
+use crate::extractors::{api_key, resolve_ip};
+use crate::response::ApiError;
+
+// In-memory token-bucket rate limiting, configured under `[default.rate_limit]` in
+// Rocket.toml. Each client (resolved IP, or configured API key when `by_api_key` is set)
+// gets one bucket per group; a request matching none of the `groups` path prefixes uses the
+// default budget. Buckets hold `burst` tokens and refill at `per_minute`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub by_api_key: bool,
+    /// Path prefixes that are never limited.
+    pub exempt: Vec<String>,
+    pub burst: u32,
+    pub per_minute: u32,
+    pub groups: Vec<RouteGroup>,
+}
+
+#[derive(Deserialize)]
+pub struct RouteGroup {
+    pub name: String,
+    /// Path prefixes, matched on whole segments: "/hash" covers "/hash/md5/x", not "/hashes".
+    pub paths: Vec<String>,
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            by_api_key: false,
+            exempt: vec!["/ping".to_string()],
+            burst: 60,
+            per_minute: 120,
+            groups: vec![RouteGroup {
+                name: "heavy".to_string(),
+                paths: ["/qr", "/barcode", "/datamatrix", "/aztec", "/pdf417", "/labels", "/hash", "/delay"].map(String::from).to_vec(),
+                burst: 10,
+                per_minute: 20,
+            }],
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<(usize, String), Bucket>>,
+}
+
+/// Outcome for one request, kept in the request-local cache for the response headers.
+#[derive(Clone, Copy)]
+struct Decision {
+    group: usize,
+    limit: u32,
+    remaining: u32,
+    /// Seconds until the bucket is full again.
+    reset: u64,
+    /// Set when the request was rejected: seconds until a token is available.
+    retry_after: Option<u64>,
+}
+
+struct Verdict(Option<Decision>);
+
+/// What a rejected request asked for before `check` rerouted it, so metrics and the
+/// access log report the original method, path and route rather than the rejection route.
+pub struct Rerouted {
+    pub method: Method,
+    pub uri: Origin<'static>,
+    /// Template of the route the request would have matched, if any.
+    pub route: Option<String>,
+}
+
+struct Original(Option<Rerouted>);
+
+pub fn rerouted<'r>(req: &'r Request<'_>) -> Option<&'r Rerouted> {
+    req.local_cache(|| Original(None)).0.as_ref()
+}
+
+// Rejected requests are rerouted here, since fairings can't answer requests themselves.
+const REJECTED_PATH: &str = "/rate-limited";
+
+// Hard cap on tracked clients. A new client arriving at a full table triggers a sweep
+// down to `SWEPT_BUCKETS`, so the O(n) sweep runs at most once per thousand new clients
+// instead of on every request.
+const MAX_BUCKETS: usize = 10_000;
+const SWEPT_BUCKETS: usize = MAX_BUCKETS * 9 / 10;
+
+impl RateLimiter {
+    /// Reads the config at ignition; when enabled, attaches the request/response hooks
+    /// and mounts the rejection route. A malformed `[rate_limit]` table aborts launch.
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Rate limiting", |rocket| async {
+            let config = match rocket.figment().extract_inner::<RateLimitConfig>("rate_limit") {
+                Ok(config) => config,
+                Err(e) if e.missing() => RateLimitConfig::default(),
+                Err(e) => {
+                    error!("invalid [rate_limit] config: {}", e);
+                    return Err(rocket);
+                }
+            };
+            if !config.enabled {
+                return Ok(rocket);
+            }
+            let limiter = RateLimiter { config, buckets: Mutex::new(HashMap::new()) };
+            Ok(rocket
+                .manage(limiter)
+                .mount("/", routes![rejected])
+                .attach(AdHoc::on_request("Rate limiting: check", |req, _| {
+                    Box::pin(async move { check(req) })
+                }))
+                .attach(AdHoc::on_response("Rate limiting: headers", |req, res| {
+                    Box::pin(async move {
+                        let Verdict(Some(d)) = req.local_cache(|| Verdict(None)) else { return };
+                        res.set_header(Header::new("RateLimit-Limit", d.limit.to_string()));
+                        res.set_header(Header::new("RateLimit-Remaining", d.remaining.to_string()));
+                        res.set_header(Header::new("RateLimit-Reset", d.reset.to_string()));
+                        if let Some(retry) = d.retry_after {
+                            res.set_header(Header::new("Retry-After", retry.to_string()));
+                        }
+                    })
+                })))
+        })
+    }
+
+    /// Index into `groups` for `path`, or `groups.len()` for the default budget.
+    fn group_for(&self, path: &str) -> usize {
+        self.config
+            .groups
+            .iter()
+            .position(|g| g.paths.iter().any(|prefix| matches_prefix(path, prefix)))
+            .unwrap_or(self.config.groups.len())
+    }
+
+    fn group_name(&self, group: usize) -> &str {
+        self.config.groups.get(group).map_or("default", |g| g.name.as_str())
+    }
+
+    fn budget(&self, group: usize) -> (u32, u32) {
+        match self.config.groups.get(group) {
+            Some(g) => (g.burst, g.per_minute),
+            None => (self.config.burst, self.config.per_minute),
+        }
+    }
+
+    fn take(&self, group: usize, client: String) -> Decision {
+        let (burst, per_minute) = self.budget(group);
+        let capacity = f64::from(burst.max(1));
+        let rate = f64::from(per_minute.max(1)) / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (group, client);
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&key) {
+            self.sweep(&mut buckets, now);
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket { tokens: capacity, updated: now });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate).min(capacity);
+        bucket.updated = now;
+
+        let retry_after = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(((1.0 - bucket.tokens) / rate).ceil() as u64)
+        };
+        Decision {
+            group,
+            limit: burst,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((capacity - bucket.tokens) / rate).ceil() as u64,
+            retry_after,
+        }
+    }
+
+    /// Drops full buckets (clients idle long enough to have refilled, so forgetting them
+    /// changes nothing), then the least recently used ones until `SWEPT_BUCKETS` remain.
+    fn sweep(&self, buckets: &mut HashMap<(usize, String), Bucket>, now: Instant) {
+        buckets.retain(|&(g, _), b| {
+            let (burst, per_minute) = self.budget(g);
+            let refilled = b.tokens + now.duration_since(b.updated).as_secs_f64() * f64::from(per_minute) / 60.0;
+            refilled < f64::from(burst)
+        });
+        if buckets.len() > SWEPT_BUCKETS {
+            let mut by_age: Vec<_> = buckets.iter().map(|(key, b)| (b.updated, key.clone())).collect();
+            by_age.sort_unstable_by_key(|(updated, _)| *updated);
+            let excess = buckets.len() - SWEPT_BUCKETS;
+            for (_, key) in by_age.into_iter().take(excess) {
+                buckets.remove(&key);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn tracked_clients(&self) -> usize {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+pub fn matches_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn check(req: &mut Request<'_>) {
+    let Some(limiter) = req.rocket().state::<RateLimiter>() else { return };
+    let path = req.uri().path().to_string();
+    if limiter.config.exempt.iter().any(|prefix| matches_prefix(&path, prefix)) {
+        return;
+    }
+
+    // Only configured keys get a bucket of their own, so made-up keys can't be rotated to
+    // dodge the per-IP budget. Keys are hashed so the table never holds raw credentials.
+    let known = |key: &&str| req.rocket().state::<ApiKeys>().is_some_and(|keys| keys.knows(key));
+    let client = match api_key(req).filter(|key| limiter.config.by_api_key && known(key)) {
+        Some(key) => format!("key:{}", hex::encode(&Sha256::digest(key.as_bytes())[..16])),
+        None => format!("ip:{}", resolve_ip(req)),
+    };
+    let decision = limiter.take(limiter.group_for(&path), client);
+    req.local_cache(|| Verdict(Some(decision)));
+
+    if decision.retry_after.is_some() {
+        let route = route_template(req);
+        req.local_cache(|| Original(Some(Rerouted { method: req.method(), uri: req.uri().clone().into_owned(), route })));
+        req.set_method(Method::Get);
+        req.set_uri(Origin::parse(REJECTED_PATH).expect("valid origin"));
+    }
+}
+
+/// Template of the route `req` would be dispatched to, picking the best rank when several
+/// paths match. Format and query constraints are ignored; this only labels metrics and logs.
+fn route_template(req: &Request<'_>) -> Option<String> {
+    let segments: Vec<&str> = req.uri().path().segments().collect();
+    req.rocket()
+        .routes()
+        .filter(|r| r.method == req.method() && template_matches(r.uri.path(), &segments))
+        .min_by_key(|r| r.rank)
+        .map(|r| r.uri.path().to_string())
+}
+
+fn template_matches(template: &str, segments: &[&str]) -> bool {
+    let mut segments = segments.iter();
+    for part in template.split('/').filter(|p| !p.is_empty()) {
+        if part.starts_with('<') && part.ends_with("..>") {
+            return true;
+        }
+        match segments.next() {
+            Some(segment) if part.starts_with('<') || part == *segment => {}
+            _ => return false,
+        }
+    }
+    segments.next().is_none()
+}
+
+/// Present only on requests `check` rejected; anything else reaching the route is a 404.
+struct Rejected(Decision);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Rejected {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        match request.local_cache(|| Verdict(None)) {
+            Verdict(Some(d)) if d.retry_after.is_some() => request::Outcome::Success(Rejected(*d)),
+            _ => request::Outcome::Forward(Status::NotFound),
+        }
+    }
+}
+
+#[get("/rate-limited")]
+fn rejected(rejected: Rejected, limiter: &State<RateLimiter>) -> ApiError {
+    let Rejected(d) = rejected;
+    let (burst, per_minute) = limiter.budget(d.group);
+    ApiError::new(
+        Status::TooManyRequests,
+        "rate_limited",
+        format!(
+            "rate limit exceeded for '{}' endpoints ({} burst, {}/min), retry in {}s",
+            limiter.group_name(d.group),
+            burst,
+            per_minute,
+            d.retry_after.unwrap_or(1)
+        ),
+    )
+}
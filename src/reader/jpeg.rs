@@ -0,0 +1,23 @@
+// JPEG to gray with `zune-jpeg`, baseline or progressive. The decoder is asked for luma
+// only, which is all a code reader needs, so color files never expand to RGB. The
+// dimensions are checked from the header before any pixels are decoded.
+
+use zune_jpeg::zune_core::colorspace::ColorSpace;
+use zune_jpeg::zune_core::options::DecoderOptions;
+use zune_jpeg::JpegDecoder;
+
+use super::{check_dimensions, Gray};
+
+pub fn decode(bytes: &[u8]) -> Result<Gray, String> {
+    let error = |e: zune_jpeg::errors::DecodeErrors| format!("unreadable JPEG: {}", e);
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::Luma);
+    let mut decoder = JpegDecoder::new_with_options(bytes, options);
+    decoder.decode_headers().map_err(error)?;
+    let (width, height) = decoder.dimensions().ok_or("JPEG has no frame header")?;
+    check_dimensions(width, height)?;
+    let pixels = decoder.decode().map_err(error)?;
+    if pixels.len() != width * height {
+        return Err("JPEG color space can't be read as gray".to_string());
+    }
+    Ok(Gray { width, height, pixels })
+}
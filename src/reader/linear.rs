@@ -0,0 +1,506 @@
+// Scanline reader for the 1D symbologies /barcode generates. Rows (and columns, for codes
+// turned on their side) are cut into bar/space widths and each decoder looks for its start
+// pattern, reads characters by comparing widths against the encoder's own tables, and
+// checks the quiet zone, stop pattern and check characters before reporting anything.
+// Hits are grouped by text and the one seen on the most scanlines wins.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use barcoders::sym::codabar::Codabar;
+use barcoders::sym::code128::Code128;
+use barcoders::sym::code39::Code39;
+use barcoders::sym::code93::Code93;
+
+use super::{Bitmap, Decoded};
+use crate::barcode::Symbology;
+use crate::gs1;
+
+/// Quiet zone required before a start pattern and after a stop pattern, in modules. Lower
+/// than the symbology specs ask for; the image edge always counts.
+const QUIET: f64 = 3.0;
+/// ITF's wide elements are themselves up to three modules, so its quiet zone has to be wider
+/// or the start and stop patterns match inside the symbol.
+const ITF_QUIET: f64 = 5.0;
+/// Width given to the margin runs at either end of a scanline that reaches the image edge.
+const EDGE: usize = 1 << 20;
+
+pub fn decode(bitmap: &Bitmap, only: Option<Symbology>) -> Result<Decoded, String> {
+    let mut hits: HashMap<(&'static str, String), Hit> = HashMap::new();
+    for transposed in [false, true] {
+        let flipped;
+        let image = if transposed {
+            flipped = bitmap.transposed();
+            &flipped
+        } else {
+            bitmap
+        };
+        let step = (image.height / 128).max(1);
+        for y in (0..image.height).step_by(step) {
+            let (mut runs, mut starts) = scanline(image, y);
+            for reversed in [false, true] {
+                if reversed {
+                    runs.reverse();
+                    starts.reverse();
+                }
+                let mut i = 1;
+                while i + 1 < runs.len() {
+                    let Some((symbology, text, end)) = read(&runs, i, only) else {
+                        i += 2;
+                        continue;
+                    };
+                    // Pixel span from the first bar's leading edge to the last bar's trailing edge.
+                    let (x0, x1) = if reversed { (starts[end - 1], starts[i] + runs[i]) } else { (starts[i], starts[end]) };
+                    let hit = hits.entry((symbology, text)).or_insert(Hit { count: 0, rect: [usize::MAX, usize::MAX, 0, 0] });
+                    let (px0, py0, px1, py1) = if transposed { (y, x0, y + 1, x1) } else { (x0, y, x1, y + 1) };
+                    hit.count += 1;
+                    hit.rect = [hit.rect[0].min(px0), hit.rect[1].min(py0), hit.rect[2].max(px1), hit.rect[3].max(py1)];
+                    i = end + 1;
+                }
+            }
+        }
+    }
+    let ((symbology, text), hit) = hits.into_iter().max_by_key(|(key, hit)| (hit.count, key.1.len(), key.clone())).ok_or("no barcode found")?;
+    let [x0, y0, x1, y1] = hit.rect.map(|v| v as f64);
+    Ok(Decoded { text, symbology, corners: [(x0, y0), (x1, y0), (x1, y1), (x0, y1)], qr: None })
+}
+
+struct Hit {
+    count: u32,
+    /// x0, y0, x1, y1 in image pixels.
+    rect: [usize; 4],
+}
+
+/// Run lengths along row `y`, alternating light and dark and starting and ending light,
+/// with each run's start x. The image edge beyond the end runs counts as quiet zone.
+fn scanline(image: &Bitmap, y: usize) -> (Vec<usize>, Vec<usize>) {
+    let (mut runs, mut starts) = (vec![EDGE], vec![0]);
+    for x in 0..image.width {
+        let dark = image.is_dark(x, y);
+        if dark != (runs.len() % 2 == 0) {
+            runs.push(0);
+            starts.push(x);
+        }
+        *runs.last_mut().unwrap() += 1;
+    }
+    if runs.len() % 2 == 0 {
+        runs.push(0);
+        starts.push(image.width);
+    }
+    *runs.last_mut().unwrap() += EDGE;
+    (runs, starts)
+}
+
+/// Tries each enabled decoder at bar `i`; returns the symbology, text and the index of the
+/// trailing quiet-zone run.
+fn read(runs: &[usize], i: usize, only: Option<Symbology>) -> Option<(&'static str, String, usize)> {
+    use Symbology::*;
+    let wants = |list: &[Symbology]| only.is_none_or(|s| list.contains(&s));
+    if wants(&[Code128, Gs1_128]) {
+        if let Some(hit) = code128(runs, i).filter(|hit| only.is_none_or(|s| (s == Gs1_128) == (hit.0 == "gs1-128"))) {
+            return Some(hit);
+        }
+    }
+    if wants(&[Ean13, UpcA, Ean8]) {
+        if let Some(hit) = ean(runs, i, only) {
+            return Some(hit);
+        }
+    }
+    if wants(&[Itf, Itf14]) {
+        if let Some(hit) = itf(runs, i).filter(|hit| only != Some(Itf14) || hit.0 == "itf14") {
+            return Some(hit);
+        }
+    }
+    if wants(&[Code39]) {
+        if let Some(hit) = code39(runs, i) {
+            return Some(hit);
+        }
+    }
+    if wants(&[Code93]) {
+        if let Some(hit) = code93(runs, i) {
+            return Some(hit);
+        }
+    }
+    if wants(&[Codabar]) {
+        return codabar(runs, i);
+    }
+    None
+}
+
+/// Bar/space patterns, in modules, taken from the encoders so reading matches writing.
+struct Tables {
+    /// Code128 values 0-105, then the stop pattern's first six elements.
+    code128: Vec<Vec<u8>>,
+    /// Code93 values 0-46, then the start/stop pattern.
+    code93: Vec<Vec<u8>>,
+    /// Code39 characters with their wide elements, `*` last.
+    code39: Vec<(char, Vec<bool>)>,
+    codabar: Vec<(char, Vec<bool>)>,
+}
+
+fn widths(bits: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::new();
+    for (i, &bit) in bits.iter().enumerate() {
+        match out.last_mut() {
+            Some(w) if i > 0 && bits[i - 1] == bit => *w += 1,
+            _ => out.push(1),
+        }
+    }
+    out
+}
+
+const CODE39_CHARS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+const CODABAR_CHARS: &str = "0123456789-$:/.+ABCD";
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let code128 = |data: &str| Code128::new(data).expect("valid Code128").encode();
+        let mut c128: Vec<Vec<u8>> = (0..100).map(|v| widths(&code128(&format!("Ć{:02}", v))[11..22])).collect();
+        c128.push(widths(&code128("ĆƁA")[11..22]));
+        c128.push(widths(&code128("ĆÀA")[11..22]));
+        c128.push(widths(&code128("Ć\u{0179}00")[11..22]));
+        for start in ["ÀA", "ƁA", "Ć00"] {
+            c128.push(widths(&code128(start)[..11]));
+        }
+        let stop = code128("ÀA");
+        c128.push(widths(&stop[stop.len() - 13..stop.len() - 2]));
+
+        // Code93's shift characters stand in for full ASCII; barcoders spells them ( ) [ and
+        // repeats [ for the last one, so that pattern is written out here.
+        let code93 = |c: char| Code93::new(c.to_string()).expect("valid Code93").encode();
+        let mut c93: Vec<Vec<u8>> = CODE39_CHARS.chars().chain("()[".chars()).map(|c| widths(&code93(c)[9..18])).collect();
+        c93.push(widths(&[1, 0, 0, 1, 1, 0, 0, 1, 0]));
+        c93.push(widths(&code93('0')[..9]));
+
+        let wide = |w: Vec<u8>| w.into_iter().map(|w| w > 1).collect::<Vec<bool>>();
+        let mut c39: Vec<(char, Vec<bool>)> = CODE39_CHARS
+            .chars()
+            .map(|c| (c, wide(widths(&Code39::new(c.to_string()).expect("valid Code39").encode()[13..25]))))
+            .collect();
+        c39.push(('*', wide(widths(&Code39::new("0").expect("valid Code39").encode()[..12]))));
+
+        let codabar = CODABAR_CHARS
+            .chars()
+            .map(|c| {
+                let runs = widths(&Codabar::new(format!("A{}A", c)).expect("valid Codabar").encode());
+                (c, wide(runs[8..15].to_vec()))
+            })
+            .collect();
+        Tables { code128: c128, code93: c93, code39: c39, codabar }
+    })
+}
+
+/// Index of the pattern closest to `runs`, scaled to `modules` in total, if close enough.
+fn closest(runs: &[usize], modules: usize, patterns: &[Vec<u8>]) -> Option<usize> {
+    let unit = runs.iter().sum::<usize>() as f64 / modules as f64;
+    patterns
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.len() == runs.len())
+        .map(|(i, p)| (runs.iter().zip(p.iter()).map(|(&r, &p)| (r as f64 / unit - p as f64).abs()).sum::<f64>(), i))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .filter(|&(distance, _)| distance < 0.4 * runs.len() as f64)
+        .map(|(_, i)| i)
+}
+
+/// Wide/narrow split around the midpoint of the narrowest and widest element.
+fn wide_narrow(runs: &[usize]) -> Option<Vec<bool>> {
+    let (min, max) = (*runs.iter().min()?, *runs.iter().max()?);
+    if max * 2 < min * 3 {
+        return None;
+    }
+    let threshold = (min + max) as f64 / 2.0;
+    Some(runs.iter().map(|&r| r as f64 > threshold).collect())
+}
+
+fn quiet(run: usize, unit: f64) -> bool {
+    run as f64 >= QUIET * unit
+}
+
+fn code128(runs: &[usize], i: usize) -> Option<(&'static str, String, usize)> {
+    let t = tables();
+    let start = closest(runs.get(i..i + 6)?, 11, &t.code128)?;
+    let unit = runs[i..i + 6].iter().sum::<usize>() as f64 / 11.0;
+    if !(103..=105).contains(&start) || !quiet(runs[i - 1], unit) {
+        return None;
+    }
+    let mut values = vec![start];
+    let mut k = i + 6;
+    loop {
+        let value = closest(runs.get(k..k + 6)?, 11, &t.code128)?;
+        if value == 106 {
+            // The stop pattern's seventh element is a two-module bar.
+            let last = *runs.get(k + 6)? as f64 / unit;
+            if !(1.5..2.5).contains(&last) || !quiet(runs[k + 7], unit) {
+                return None;
+            }
+            k += 7;
+            break;
+        }
+        if value >= 103 {
+            return None;
+        }
+        values.push(value);
+        k += 6;
+    }
+    let check = values.pop()?;
+    let sum = values.iter().enumerate().map(|(n, &v)| v * n.max(1)).sum::<usize>();
+    if values.len() < 2 || sum % 103 != check {
+        return None;
+    }
+
+    // 0 = A, 1 = B, 2 = C; `shift` reads one character from the other of A and B.
+    let mut set = start - 103;
+    let mut shift = false;
+    let (mut text, mut gs1) = (String::new(), false);
+    for (n, &v) in values[1..].iter().enumerate() {
+        let current = if shift { 1 - set } else { set };
+        shift = false;
+        match (current, v) {
+            (_, 102) if n == 0 => gs1 = true,
+            (_, 102) => text.push('\x1d'),
+            (2, 0..=99) => text.push_str(&format!("{:02}", v)),
+            (0 | 2, 100) => set = 1,
+            (1 | 2, 101) => set = 0,
+            (0 | 1, 99) => set = 2,
+            (0 | 1, 98) => shift = true,
+            (0, 0..=63) | (1, 0..=95) => text.push((v as u8 + 32) as char),
+            (0, 64..=95) => text.push((v as u8 - 64) as char),
+            // FNC2-4 carry no text.
+            _ => {}
+        }
+    }
+    if gs1 {
+        let elements = gs1::parse_encoded(&text, '\x1d').ok()?;
+        return Some(("gs1-128", gs1::human_readable(&elements), k));
+    }
+    Some(("code128", text, k))
+}
+
+/// EAN/UPC digit patterns as set L (space first); G is the reverse and R the same widths
+/// bar first.
+const EAN_L: [[u8; 4]; 10] =
+    [[3, 2, 1, 1], [2, 2, 2, 1], [2, 1, 2, 2], [1, 4, 1, 1], [1, 1, 3, 2], [1, 2, 3, 1], [1, 1, 1, 4], [1, 3, 1, 2], [1, 2, 1, 3], [3, 1, 1, 2]];
+/// Which of the six left-hand EAN-13 digits use set G, by the implied first digit.
+const EAN_PARITY: [&str; 10] = ["LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL", "LGGLGL"];
+
+/// `n` guard elements of one module each.
+fn guard(runs: &[usize], unit: f64) -> bool {
+    runs.iter().all(|&r| (0.5..1.6).contains(&(r as f64 / unit)))
+}
+
+fn ean(runs: &[usize], i: usize, only: Option<Symbology>) -> Option<(&'static str, String, usize)> {
+    let start = runs.get(i..i + 3)?;
+    let unit = start.iter().sum::<usize>() as f64 / 3.0;
+    if !guard(start, unit) || !quiet(runs[i - 1], unit) {
+        return None;
+    }
+    let mut patterns: Vec<Vec<u8>> = EAN_L.iter().map(|p| p.to_vec()).collect();
+    patterns.extend(EAN_L.iter().map(|p| p.iter().rev().cloned().collect::<Vec<u8>>()));
+
+    for half in [6, 4] {
+        let mut digits = Vec::new();
+        let mut parity = String::new();
+        let mut k = i + 3;
+        let mut ok = true;
+        for side in 0..2 {
+            for _ in 0..half {
+                let Some(digit) = runs.get(k..k + 4).and_then(|r| closest(r, 7, &patterns[..if side == 0 { 20 } else { 10 }])) else {
+                    ok = false;
+                    break;
+                };
+                digits.push((digit % 10) as u8);
+                parity.push(if digit >= 10 { 'G' } else { 'L' });
+                k += 4;
+            }
+            // Middle guard after the left half, end guard after the right.
+            let n = if side == 0 { 5 } else { 3 };
+            if !ok || !runs.get(k..k + n).is_some_and(|r| guard(r, unit)) {
+                ok = false;
+                break;
+            }
+            k += n;
+        }
+        if !ok || !quiet(runs[k], unit) {
+            continue;
+        }
+        let parity = &parity[..half];
+        let mut text: String = if half == 6 {
+            let first = EAN_PARITY.iter().position(|&p| p == parity)?;
+            std::iter::once(first as u8).chain(digits).map(|d| (b'0' + d) as char).collect()
+        } else if parity == "LLLL" {
+            digits.iter().map(|d| (b'0' + d) as char).collect()
+        } else {
+            return None;
+        };
+        let (body, check) = text.split_at(text.len() - 1);
+        if gs1::check_digit(body) != check.as_bytes()[0] - b'0' {
+            return None;
+        }
+        let symbology = match half {
+            4 => "ean8",
+            // UPC-A is EAN-13 with a leading zero, unless EAN-13 was asked for.
+            _ if text.starts_with('0') && only != Some(Symbology::Ean13) => {
+                text.remove(0);
+                "upca"
+            }
+            _ => "ean13",
+        };
+        let wanted = match only {
+            Some(Symbology::Ean8) => symbology == "ean8",
+            Some(Symbology::UpcA) => symbology == "upca",
+            Some(Symbology::Ean13) => symbology == "ean13",
+            _ => true,
+        };
+        return wanted.then_some((symbology, text, k));
+    }
+    None
+}
+
+/// Interleaved 2 of 5 digit patterns, true = wide.
+const ITF_DIGITS: [[bool; 5]; 10] = {
+    const N: bool = false;
+    const W: bool = true;
+    [[N, N, W, W, N], [W, N, N, N, W], [N, W, N, N, W], [W, W, N, N, N], [N, N, W, N, W], [W, N, W, N, N], [N, W, W, N, N], [N, N, N, W, W], [W, N, N, W, N], [N, W, N, W, N]]
+};
+
+fn itf(runs: &[usize], i: usize) -> Option<(&'static str, String, usize)> {
+    let start = runs.get(i..i + 4)?;
+    let unit = start.iter().sum::<usize>() as f64 / 4.0;
+    if !guard(start, unit) || (runs[i - 1] as f64) < ITF_QUIET * unit {
+        return None;
+    }
+    let mut text = String::new();
+    let mut k = i + 4;
+    loop {
+        // Stop: wide bar, narrow space, narrow bar, then the quiet zone.
+        let stop = runs.get(k..k + 4)?;
+        if stop[0] as f64 > 1.5 * unit && guard(&stop[1..3], unit) && stop[3] as f64 >= ITF_QUIET * unit {
+            k += 3;
+            break;
+        }
+        let group = runs.get(k..k + 10)?;
+        // Bars carry the first digit of the pair, spaces the second.
+        for offset in 0..2 {
+            let elements: Vec<usize> = group.iter().skip(offset).step_by(2).cloned().collect();
+            let wide = wide_narrow(&elements)?;
+            let digit = ITF_DIGITS.iter().position(|p| p[..] == wide[..])?;
+            text.push((b'0' + digit as u8) as char);
+        }
+        k += 10;
+    }
+    if text.is_empty() {
+        return None;
+    }
+    let itf14 = text.len() == 14 && gs1::check_digit(&text[..13]) == text.as_bytes()[13] - b'0';
+    Some((if itf14 { "itf14" } else { "itf" }, text, k))
+}
+
+/// Reads `width`-element characters separated by a one-module gap, from a start character
+/// to a stop character, by their wide/narrow pattern.
+fn delimited(runs: &[usize], i: usize, width: usize, table: &[(char, Vec<bool>)], guards: &str) -> Option<(String, usize)> {
+    let lookup = |k: usize| -> Option<char> {
+        let wide = wide_narrow(runs.get(k..k + width)?)?;
+        table.iter().find(|(_, p)| *p == wide).map(|&(c, _)| c)
+    };
+    let first = lookup(i).filter(|c| guards.contains(*c))?;
+    let narrow = *runs[i..i + width].iter().min()? as f64;
+    if !quiet(runs[i - 1], narrow) {
+        return None;
+    }
+    let mut text = String::from(first);
+    let mut k = i + width;
+    loop {
+        // The inter-character gap must stay narrow.
+        if *runs.get(k)? as f64 > 2.0 * narrow {
+            return None;
+        }
+        let c = lookup(k + 1)?;
+        text.push(c);
+        k += 1 + width;
+        if guards.contains(c) {
+            break;
+        }
+    }
+    if text.len() < 3 || !quiet(*runs.get(k)?, narrow) {
+        return None;
+    }
+    Some((text, k))
+}
+
+fn code39(runs: &[usize], i: usize) -> Option<(&'static str, String, usize)> {
+    let (text, end) = delimited(runs, i, 9, &tables().code39, "*")?;
+    Some(("code39", text[1..text.len() - 1].to_string(), end))
+}
+
+/// Codabar start/stop characters are dropped from the text, as most scanners do.
+fn codabar(runs: &[usize], i: usize) -> Option<(&'static str, String, usize)> {
+    let (text, end) = delimited(runs, i, 7, &tables().codabar, "ABCD")?;
+    Some(("codabar", text[1..text.len() - 1].to_string(), end))
+}
+
+fn code93(runs: &[usize], i: usize) -> Option<(&'static str, String, usize)> {
+    let t = tables();
+    let start = runs.get(i..i + 6)?;
+    let unit = start.iter().sum::<usize>() as f64 / 9.0;
+    if closest(start, 9, &t.code93)? != 47 || !quiet(runs[i - 1], unit) {
+        return None;
+    }
+    let mut values = Vec::new();
+    let mut k = i + 6;
+    loop {
+        let value = closest(runs.get(k..k + 6)?, 9, &t.code93)?;
+        k += 6;
+        if value == 47 {
+            // Stop, then a one-module termination bar.
+            if !guard(runs.get(k..k + 1)?, unit) || !quiet(*runs.get(k + 1)?, unit) {
+                return None;
+            }
+            k += 1;
+            break;
+        }
+        values.push(value);
+    }
+    if values.len() < 3 {
+        return None;
+    }
+    let checksum = |data: &[usize], cycle: usize| {
+        data.iter().rev().enumerate().map(|(n, &v)| v * (n % cycle + 1)).sum::<usize>() % 47
+    };
+    let n = values.len();
+    if checksum(&values[..n - 2], 20) != values[n - 2] || checksum(&values[..n - 1], 15) != values[n - 1] {
+        return None;
+    }
+
+    // Values 43-46 are the ($) (%) (/) (+) shifts of full-ASCII Code93.
+    let mut text = String::new();
+    let mut data = values[..n - 2].iter();
+    while let Some(&v) = data.next() {
+        if v < 43 {
+            text.push(CODE39_CHARS.as_bytes()[v] as char);
+            continue;
+        }
+        let next = *data.next()?;
+        if !(10..36).contains(&next) {
+            return None;
+        }
+        let letter = (next - 10) as u8;
+        text.push(match (v, letter) {
+            (43, _) => (letter + 1) as char,
+            (44, 0..=4) => (letter + 27) as char,
+            (44, 5..=9) => (letter - 5 + b';') as char,
+            (44, 10..=14) => (letter - 10 + b'[') as char,
+            (44, 15..=19) => (letter - 15 + b'{') as char,
+            (44, 20) => '\0',
+            (44, 21) => '@',
+            (44, 22) => '`',
+            (44, _) => '\x7f',
+            (45, 0..=14) => (letter + b'!') as char,
+            (45, 25) => ':',
+            (46, _) => (letter + b'a') as char,
+            _ => return None,
+        });
+    }
+    Some(("code93", text, k))
+}
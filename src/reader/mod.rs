@@ -0,0 +1,138 @@
+// Reading codes back out of uploaded images, for `/qr/decode` and `/barcode/decode`.
+// Images are decoded by the `png` and `zune-jpeg` crates straight to 8-bit gray; the
+// local-contrast binarizer, the QR reader and the scanline readers for the 1D
+// symbologies are in-tree. It's aimed at the images our generators and label printers
+// produce, scanned or photographed roughly square-on; it doesn't try to undo curvature,
+// blur or heavy perspective.
+
+mod jpeg;
+pub mod linear;
+mod png;
+pub mod qr;
+
+/// Decoded images larger than this (4 MP, e.g. 2048x2048) are refused before any pixel
+/// buffer is allocated.
+pub const MAX_PIXELS: usize = 4_194_304;
+
+/// 8-bit luma, row-major.
+pub struct Gray {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// PNG or JPEG by signature, converted to gray.
+pub fn decode_image(bytes: &[u8]) -> Result<Gray, String> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png::decode(bytes)
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        jpeg::decode(bytes)
+    } else {
+        Err("expected a PNG or JPEG image".to_string())
+    }
+}
+
+/// Rejects zero or oversized dimensions from an image header.
+fn check_dimensions(width: usize, height: usize) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err("image has no pixels".to_string());
+    }
+    if width.saturating_mul(height) > MAX_PIXELS {
+        return Err(format!("image is {}x{} px; at most {} pixels are decoded", width, height, MAX_PIXELS));
+    }
+    Ok(())
+}
+
+/// Dark/light pixels after thresholding.
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    dark: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.dark[y * self.width + x]
+    }
+
+    /// Signed coordinates, light outside the image.
+    pub fn at(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && self.is_dark(x as usize, y as usize)
+    }
+
+    /// The same image mirrored along the diagonal, so column scans can reuse row code.
+    pub fn transposed(&self) -> Bitmap {
+        let dark = (0..self.width).flat_map(|x| (0..self.height).map(move |y| (x, y))).map(|(x, y)| self.is_dark(x, y)).collect();
+        Bitmap { width: self.height, height: self.width, dark }
+    }
+}
+
+const BLOCK: usize = 8;
+/// Blocks whose pixels span less than this are treated as flat (all light or all dark).
+const MIN_DYNAMIC_RANGE: u8 = 24;
+
+/// Thresholds each 8x8 block against the average of the 5x5 blocks around it, so uneven
+/// lighting across a photo doesn't swallow half the code. Flat blocks (inside a large
+/// module, or background) borrow their level from their neighbours.
+pub fn binarize(image: &Gray) -> Bitmap {
+    let (w, h) = (image.width, image.height);
+    let (bw, bh) = (w.div_ceil(BLOCK), h.div_ceil(BLOCK));
+    let mut levels = vec![0u32; bw * bh];
+    for by in 0..bh {
+        for bx in 0..bw {
+            let (mut min, mut max, mut sum, mut n) = (u8::MAX, 0u8, 0u32, 0u32);
+            for y in by * BLOCK..((by + 1) * BLOCK).min(h) {
+                for &v in &image.pixels[y * w + bx * BLOCK..y * w + ((bx + 1) * BLOCK).min(w)] {
+                    min = min.min(v);
+                    max = max.max(v);
+                    sum += v as u32;
+                    n += 1;
+                }
+            }
+            let mut level = sum / n;
+            if max - min <= MIN_DYNAMIC_RANGE {
+                // Flat: assume light unless the neighbours above/left say this is darker.
+                level = min as u32 / 2;
+                if bx > 0 && by > 0 {
+                    let around = (levels[(by - 1) * bw + bx] + 2 * levels[by * bw + bx - 1] + levels[(by - 1) * bw + bx - 1]) / 4;
+                    if (min as u32) < around {
+                        level = around;
+                    }
+                }
+            }
+            levels[by * bw + bx] = level;
+        }
+    }
+
+    let mut dark = vec![false; w * h];
+    for by in 0..bh {
+        for bx in 0..bw {
+            let (x0, x1) = (bx.saturating_sub(2), (bx + 2).min(bw - 1));
+            let (y0, y1) = (by.saturating_sub(2), (by + 2).min(bh - 1));
+            let (mut sum, mut n) = (0, 0);
+            for ny in y0..=y1 {
+                for nx in x0..=x1 {
+                    sum += levels[ny * bw + nx];
+                    n += 1;
+                }
+            }
+            let threshold = sum / n;
+            for y in by * BLOCK..((by + 1) * BLOCK).min(h) {
+                for x in bx * BLOCK..((bx + 1) * BLOCK).min(w) {
+                    dark[y * w + x] = (image.pixels[y * w + x] as u32) <= threshold;
+                }
+            }
+        }
+    }
+    Bitmap { width: w, height: h, dark }
+}
+
+/// A code found in an image. Corners run clockwise from the symbol's top-left as read,
+/// in image pixels; 1D codes report the box spanned by the scanlines that read them.
+pub struct Decoded {
+    pub text: String,
+    pub symbology: &'static str,
+    pub corners: [(f64, f64); 4],
+    /// QR version and error correction level.
+    pub qr: Option<(u8, char)>,
+}
@@ -0,0 +1,46 @@
+// PNG to gray with the `png` crate: every color type and bit depth is normalized to 8-bit
+// samples, and each row is converted to luma as it's decoded, with alpha composited onto
+// white (transparent label backgrounds are common). The dimensions are checked before the
+// first row, so a small upload can't claim an arbitrarily large canvas.
+
+use super::{check_dimensions, Gray};
+
+pub fn decode(bytes: &[u8]) -> Result<Gray, String> {
+    let error = |e: png::DecodingError| format!("unreadable PNG: {}", e);
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(error)?;
+    let (width, height) = (reader.info().width as usize, reader.info().height as usize);
+    check_dimensions(width, height)?;
+    let (color, _) = reader.output_color_type();
+
+    let mut pixels = vec![0u8; width * height];
+    let mut row_gray = Vec::with_capacity(width);
+    let mut y = 0;
+    while let Some(row) = reader.next_interlaced_row().map_err(error)? {
+        row_gray.clear();
+        row_gray.extend(row.data().chunks_exact(color.samples()).map(|px| luma(color, px)));
+        match row.interlace() {
+            png::InterlaceInfo::Adam7(pass) => png::expand_interlaced_row(&mut pixels, width, &row_gray, pass, 8),
+            png::InterlaceInfo::Null(_) => {
+                if let Some(out) = pixels.get_mut(y * width..(y + 1) * width) {
+                    out.copy_from_slice(&row_gray[..width]);
+                }
+                y += 1;
+            }
+        }
+    }
+    Ok(Gray { width, height, pixels })
+}
+
+/// One 8-bit pixel (palettes already expanded) as luma composited onto white.
+fn luma(color: png::ColorType, px: &[u8]) -> u8 {
+    let rgb = |r: u8, g: u8, b: u8| (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+    let (value, alpha) = match color {
+        png::ColorType::GrayscaleAlpha => (px[0] as u32, px[1] as u32),
+        png::ColorType::Rgb => (rgb(px[0], px[1], px[2]), 255),
+        png::ColorType::Rgba => (rgb(px[0], px[1], px[2]), px[3] as u32),
+        _ => (px[0] as u32, 255),
+    };
+    ((value * alpha + 255 * (255 - alpha)) / 255) as u8
+}
@@ -0,0 +1,679 @@
+// QR reader: find the three finder patterns, pick the grid size, refine with the
+// bottom-right alignment pattern when there is one, sample every module through a
+// perspective transform, then undo masking, interleaving and errors (Reed-Solomon over
+// GF(256)) and parse the segments. Model 2 symbols only; no Micro QR.
+
+use qrcode::bits::Bits;
+use qrcode::canvas::is_functional;
+use qrcode::{EcLevel, Version};
+
+use super::{Bitmap, Decoded};
+
+type Point = (f64, f64);
+
+pub fn decode(bitmap: &Bitmap) -> Result<Decoded, String> {
+    let finders = find_finders(bitmap);
+    let (tl, tr, bl) = best_triple(&finders).ok_or("no QR code found")?;
+    let module = (tl.size + tr.size + bl.size) / 3.0;
+    let estimate = ((dist(tl.center, tr.center) + dist(tl.center, bl.center)) / 2.0 / module + 7.0).round() as i64;
+    // Nearest valid size first, then its neighbours in case the estimate is off by one version.
+    let nearest = ((estimate - 17) as f64 / 4.0).round() as i64 * 4 + 17;
+    let mut sizes = vec![nearest, nearest + 4, nearest - 4];
+    sizes.retain(|&d| (21..=177).contains(&d));
+
+    let mut tried = Vec::new();
+    while let Some(dim) = sizes.first().copied() {
+        sizes.remove(0);
+        if tried.contains(&dim) {
+            continue;
+        }
+        tried.push(dim);
+        let grid = Grid::locate(bitmap, tl.center, tr.center, bl.center, dim as usize);
+        let matrix = grid.sample(bitmap);
+        // Version information wins over the measured size from version 7 up.
+        if dim >= 45 {
+            if let Some(version) = read_version(&matrix) {
+                let expected = 17 + 4 * version as i64;
+                if expected != dim {
+                    sizes.insert(0, expected);
+                    continue;
+                }
+            }
+        }
+        // A mirrored symbol reads correctly once transposed.
+        for matrix in [matrix.clone(), matrix.transposed()] {
+            if let Ok((text, version, ec)) = read(&matrix) {
+                let d = dim as f64;
+                return Ok(Decoded {
+                    text,
+                    symbology: "qr",
+                    corners: [grid.map(0.0, 0.0), grid.map(d, 0.0), grid.map(d, d), grid.map(0.0, d)],
+                    qr: Some((version, ec)),
+                });
+            }
+        }
+    }
+    Err("found a QR code but couldn't read it; try a sharper or larger image".to_string())
+}
+
+/// A finder pattern candidate: centre, module size in pixels, and how many scans hit it.
+#[derive(Clone, Copy)]
+struct Finder {
+    center: Point,
+    size: f64,
+    hits: u32,
+}
+
+fn dist(a: Point, b: Point) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Dark-light-dark-light-dark runs in the ratio 1:1:3:1:1, within half a module.
+fn finder_ratio(runs: &[usize; 5]) -> bool {
+    let total: usize = runs.iter().sum();
+    if total < 7 || runs.contains(&0) {
+        return false;
+    }
+    let module = total as f64 / 7.0;
+    let slack = module / 2.0;
+    runs.iter().zip([1.0, 1.0, 3.0, 1.0, 1.0]).all(|(&r, w)| (r as f64 - w * module).abs() < w * slack)
+}
+
+/// Checks for the finder ratio through (x, y) along (dx, dy); returns the centre's offset
+/// along that axis from the start pixel's edge, and the pattern width.
+fn cross_check(bitmap: &Bitmap, x: i64, y: i64, dx: i64, dy: i64, limit: usize) -> Option<(f64, usize)> {
+    let dark = |t: i64| bitmap.at(x + dx * t, y + dy * t);
+    if !dark(0) {
+        return None;
+    }
+    let mut runs = [0usize; 5];
+    // Backwards from the start pixel: centre, light ring, outer dark ring; then forwards.
+    let mut t = 0;
+    for (i, want) in [(2, true), (1, false), (0, true)] {
+        while dark(t) == want && runs[i] <= limit {
+            runs[i] += 1;
+            t -= 1;
+        }
+    }
+    t = 1;
+    for (i, want) in [(2, true), (3, false), (4, true)] {
+        while dark(t) == want && runs[i] <= limit {
+            runs[i] += 1;
+            t += 1;
+        }
+    }
+    if !finder_ratio(&runs) {
+        return None;
+    }
+    let center_end = t - runs[4] as i64 - runs[3] as i64;
+    Some((center_end as f64 - runs[2] as f64 / 2.0, runs.iter().sum()))
+}
+
+fn find_finders(bitmap: &Bitmap) -> Vec<Finder> {
+    let mut finders: Vec<Finder> = Vec::new();
+    let step = (bitmap.height / 512).max(1);
+    for y in (0..bitmap.height).step_by(step) {
+        // Runs along the row as (start, length), alternating colour.
+        let mut runs: Vec<(usize, usize, bool)> = Vec::new();
+        for x in 0..bitmap.width {
+            let dark = bitmap.is_dark(x, y);
+            match runs.last_mut() {
+                Some(run) if run.2 == dark => run.1 += 1,
+                _ => runs.push((x, 1, dark)),
+            }
+        }
+        for window in runs.windows(5) {
+            if !window[0].2 {
+                continue;
+            }
+            let lengths = [window[0].1, window[1].1, window[2].1, window[3].1, window[4].1];
+            if !finder_ratio(&lengths) {
+                continue;
+            }
+            let total: usize = lengths.iter().sum();
+            let cx = window[2].0 as i64 + window[2].1 as i64 / 2;
+            let Some((oy, height)) = cross_check(bitmap, cx, y as i64, 0, 1, total) else { continue };
+            let cy = y as f64 + oy;
+            let Some((ox, width)) = cross_check(bitmap, cx, cy as i64, 1, 0, total) else { continue };
+            if height * 5 < total * 3 || height * 3 > total * 5 {
+                continue;
+            }
+            let center = (cx as f64 + ox, cy);
+            let size = (width + height) as f64 / 14.0;
+            match finders.iter_mut().find(|f| dist(f.center, center) <= f.size * 2.0 && (f.size - size).abs() <= f.size / 2.0) {
+                Some(f) => {
+                    let n = f.hits as f64;
+                    f.center = ((f.center.0 * n + center.0) / (n + 1.0), (f.center.1 * n + center.1) / (n + 1.0));
+                    f.size = (f.size * n + size) / (n + 1.0);
+                    f.hits += 1;
+                }
+                None => finders.push(Finder { center, size, hits: 1 }),
+            }
+        }
+    }
+    finders.sort_by_key(|f| std::cmp::Reverse(f.hits));
+    finders.truncate(12);
+    finders
+}
+
+/// The three candidates that best form a right isosceles triangle of like-sized patterns,
+/// as (top-left, top-right, bottom-left) in reading order.
+fn best_triple(finders: &[Finder]) -> Option<(Finder, Finder, Finder)> {
+    let mut best: Option<(f64, [Finder; 3])> = None;
+    for i in 0..finders.len() {
+        for j in i + 1..finders.len() {
+            for k in j + 1..finders.len() {
+                let trio = [finders[i], finders[j], finders[k]];
+                let sizes = trio.map(|f| f.size);
+                let (min, max) = (sizes.iter().cloned().fold(f64::MAX, f64::min), sizes.iter().cloned().fold(0.0, f64::max));
+                if max > min * 1.5 {
+                    continue;
+                }
+                let mut sides = [dist(trio[1].center, trio[2].center), dist(trio[0].center, trio[2].center), dist(trio[0].center, trio[1].center)];
+                // The corner opposite the longest side is the top-left.
+                let corner = (0..3).max_by(|&a, &b| sides[a].total_cmp(&sides[b])).unwrap();
+                let c = sides[corner];
+                sides[corner] = 0.0;
+                sides.sort_by(|a, b| b.total_cmp(a));
+                let (a, b) = (sides[0], sides[1]);
+                if b < 10.0 * min {
+                    continue;
+                }
+                let score = (a - b).abs() / a + (c * c - a * a - b * b).abs() / (c * c);
+                if score < 0.5 && best.as_ref().is_none_or(|(s, _)| score < *s) {
+                    let tl = trio[corner];
+                    let (p, q) = (trio[(corner + 1) % 3], trio[(corner + 2) % 3]);
+                    let cross = (p.center.0 - tl.center.0) * (q.center.1 - tl.center.1) - (p.center.1 - tl.center.1) * (q.center.0 - tl.center.0);
+                    best = Some((score, if cross > 0.0 { [tl, p, q] } else { [tl, q, p] }));
+                }
+            }
+        }
+    }
+    best.map(|(_, [tl, tr, bl])| (tl, tr, bl))
+}
+
+/// Module coordinates to image pixels: a projective map, as a 3x3 matrix on (x, y, 1).
+struct Grid {
+    m: [[f64; 3]; 3],
+    dim: usize,
+}
+
+impl Grid {
+    /// Anchors the finder centres and, from version 2, the bottom-right alignment pattern;
+    /// without one the fourth corner completes a parallelogram.
+    fn locate(bitmap: &Bitmap, tl: Point, tr: Point, bl: Point, dim: usize) -> Grid {
+        let d = dim as f64;
+        let span = d - 7.0;
+        let u = ((tr.0 - tl.0) / span, (tr.1 - tl.1) / span);
+        let v = ((bl.0 - tl.0) / span, (bl.1 - tl.1) / span);
+        let corner = d - 3.5;
+        let mut fourth = ((corner, corner), (tr.0 + bl.0 - tl.0, tr.1 + bl.1 - tl.1));
+        if dim > 21 {
+            let at = d - 6.5;
+            let guess = (tl.0 + (u.0 + v.0) * (at - 3.5), tl.1 + (u.1 + v.1) * (at - 3.5));
+            if let Some(found) = find_alignment(bitmap, guess, u, v, 4.0 + d / 16.0) {
+                fourth = ((at, at), found);
+            }
+        }
+        let from = square_to_quad([(3.5, 3.5), (corner, 3.5), fourth.0, (3.5, corner)]);
+        let to = square_to_quad([tl, tr, fourth.1, bl]);
+        Grid { m: multiply(&to, &adjugate(&from)), dim }
+    }
+
+    fn map(&self, x: f64, y: f64) -> Point {
+        let m = &self.m;
+        let w = m[2][0] * x + m[2][1] * y + m[2][2];
+        ((m[0][0] * x + m[0][1] * y + m[0][2]) / w, (m[1][0] * x + m[1][1] * y + m[1][2]) / w)
+    }
+
+    fn sample(&self, bitmap: &Bitmap) -> Matrix {
+        let dim = self.dim;
+        let mut dark = vec![false; dim * dim];
+        for row in 0..dim {
+            for col in 0..dim {
+                let (x, y) = self.map(col as f64 + 0.5, row as f64 + 0.5);
+                dark[row * dim + col] = bitmap.at(x.floor() as i64, y.floor() as i64);
+            }
+        }
+        Matrix { dim, dark }
+    }
+}
+
+/// The projective map taking the unit square's corners (clockwise from the origin) to `q`.
+fn square_to_quad(q: [Point; 4]) -> [[f64; 3]; 3] {
+    let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = q;
+    let (dx3, dy3) = (x0 - x1 + x2 - x3, y0 - y1 + y2 - y3);
+    let (dx1, dx2, dy1, dy2) = (x1 - x2, x3 - x2, y1 - y2, y3 - y2);
+    let denom = dx1 * dy2 - dx2 * dy1;
+    let a13 = (dx3 * dy2 - dx2 * dy3) / denom;
+    let a23 = (dx1 * dy3 - dx3 * dy1) / denom;
+    [
+        [x1 - x0 + a13 * x1, x3 - x0 + a23 * x3, x0],
+        [y1 - y0 + a13 * y1, y3 - y0 + a23 * y3, y0],
+        [a13, a23, 1.0],
+    ]
+}
+
+/// Adjugate: the inverse up to scale, which is all a projective map needs.
+fn adjugate(m: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let c = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    [
+        [c(1, 2, 1, 2), -c(0, 2, 1, 2), c(0, 1, 1, 2)],
+        [-c(1, 2, 0, 2), c(0, 2, 0, 2), -c(0, 1, 0, 2)],
+        [c(1, 2, 0, 1), -c(0, 2, 0, 1), c(0, 1, 0, 1)],
+    ]
+}
+
+fn multiply(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// Looks around `guess` for the 5x5 alignment pattern (dark ring, light ring, dark centre),
+/// scoring each position by how many of its 25 modules match, and returns the centre of
+/// the best-scoring positions.
+fn find_alignment(bitmap: &Bitmap, guess: Point, u: Point, v: Point, radius: f64) -> Option<Point> {
+    let module = ((u.0 * u.0 + u.1 * u.1).sqrt() + (v.0 * v.0 + v.1 * v.1).sqrt()) / 2.0;
+    let reach = (radius * module).ceil() as i64;
+    let step = (module / 3.0).max(1.0);
+    let steps = (reach as f64 / step) as i64;
+    let (mut best, mut sum, mut n) = (0, (0.0, 0.0), 0.0);
+    for sy in -steps..=steps {
+        for sx in -steps..=steps {
+            let p = (guess.0 + sx as f64 * step, guess.1 + sy as f64 * step);
+            let mut score = 0;
+            for j in -2i32..=2 {
+                for i in -2i32..=2 {
+                    let want = i.abs().max(j.abs()) != 1;
+                    let (x, y) = (p.0 + u.0 * i as f64 + v.0 * j as f64, p.1 + u.1 * i as f64 + v.1 * j as f64);
+                    if bitmap.at(x.floor() as i64, y.floor() as i64) == want {
+                        score += 1;
+                    }
+                }
+            }
+            if score > best {
+                (best, sum, n) = (score, (0.0, 0.0), 0.0);
+            }
+            if score == best {
+                sum = (sum.0 + p.0, sum.1 + p.1);
+                n += 1.0;
+            }
+        }
+    }
+    (best >= 23).then(|| (sum.0 / n, sum.1 / n))
+}
+
+/// Sampled modules, row-major; `true` is dark.
+#[derive(Clone)]
+struct Matrix {
+    dim: usize,
+    dark: Vec<bool>,
+}
+
+impl Matrix {
+    /// Negative coordinates count from the far edge, as in qrcode's canvas.
+    fn get(&self, x: i16, y: i16) -> bool {
+        let d = self.dim as i16;
+        let (x, y) = (if x < 0 { x + d } else { x }, if y < 0 { y + d } else { y });
+        self.dark[y as usize * self.dim + x as usize]
+    }
+
+    fn transposed(&self) -> Matrix {
+        let d = self.dim;
+        Matrix { dim: d, dark: (0..d * d).map(|i| self.dark[(i % d) * d + i / d]).collect() }
+    }
+
+    fn number(&self, coords: &[(i16, i16)]) -> u32 {
+        coords.iter().fold(0, |acc, &(x, y)| acc << 1 | self.get(x, y) as u32)
+    }
+}
+
+const FORMAT_MAIN: [(i16, i16); 15] =
+    [(0, 8), (1, 8), (2, 8), (3, 8), (4, 8), (5, 8), (7, 8), (8, 8), (8, 7), (8, 5), (8, 4), (8, 3), (8, 2), (8, 1), (8, 0)];
+const FORMAT_SIDE: [(i16, i16); 15] =
+    [(8, -1), (8, -2), (8, -3), (8, -4), (8, -5), (8, -6), (8, -7), (-8, 8), (-7, 8), (-6, 8), (-5, 8), (-4, 8), (-3, 8), (-2, 8), (-1, 8)];
+
+/// Remainder of `data << shift` divided by `generator` over GF(2).
+fn bch(data: u32, shift: u32, generator: u32) -> u32 {
+    let degree = 31 - generator.leading_zeros();
+    let mut value = data << shift;
+    while value != 0 && 31 - value.leading_zeros() >= degree {
+        value ^= generator << (31 - value.leading_zeros() - degree);
+    }
+    data << shift | value
+}
+
+/// Error correction level and mask from the better-matching format information copy.
+fn read_format(matrix: &Matrix) -> Option<(EcLevel, u8)> {
+    let copies = [matrix.number(&FORMAT_MAIN), matrix.number(&FORMAT_SIDE)];
+    (0..32u32)
+        .flat_map(|data| copies.map(|bits| ((bch(data, 10, 0x537) ^ 0x5412 ^ bits).count_ones(), data)))
+        .min()
+        .filter(|&(distance, _)| distance <= 3)
+        .map(|(_, data)| {
+            let ec = [EcLevel::M, EcLevel::L, EcLevel::H, EcLevel::Q][(data >> 3) as usize];
+            (ec, (data & 7) as u8)
+        })
+}
+
+/// Version from either version information block, for versions 7 and up.
+fn read_version(matrix: &Matrix) -> Option<u8> {
+    let bl: Vec<(i16, i16)> = (0..6).rev().flat_map(|x| (9..12).map(move |y| (x, -y))).collect();
+    let tr: Vec<(i16, i16)> = bl.iter().map(|&(x, y)| (y, x)).collect();
+    let copies = [matrix.number(&bl), matrix.number(&tr)];
+    (7..=40u32)
+        .flat_map(|v| copies.map(|bits| ((bch(v, 12, 0x1f25) ^ bits).count_ones(), v)))
+        .min()
+        .filter(|&(distance, _)| distance <= 3)
+        .map(|(_, v)| v as u8)
+}
+
+fn mask(pattern: u8, x: usize, y: usize) -> bool {
+    match pattern {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (y / 2 + x / 3).is_multiple_of(2),
+        5 => (x * y) % 2 + (x * y) % 3 == 0,
+        6 => ((x * y) % 2 + (x * y) % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + (x * y) % 3).is_multiple_of(2),
+    }
+}
+
+/// Error correction blocks per version and level (L, M, Q, H), ISO/IEC 18004 table 9.
+const BLOCKS: [[u8; 4]; 40] = [
+    [1, 1, 1, 1], [1, 1, 1, 1], [1, 1, 2, 2], [1, 2, 2, 4], [1, 2, 4, 4],
+    [2, 4, 4, 4], [2, 4, 6, 5], [2, 4, 6, 6], [2, 5, 8, 8], [4, 5, 8, 8],
+    [4, 5, 8, 11], [4, 8, 10, 11], [4, 9, 12, 16], [4, 9, 16, 16], [6, 10, 12, 18],
+    [6, 10, 17, 16], [6, 11, 16, 19], [6, 13, 18, 21], [7, 14, 21, 25], [8, 16, 20, 25],
+    [8, 17, 23, 25], [9, 17, 23, 34], [9, 18, 25, 30], [10, 20, 27, 32], [12, 21, 29, 35],
+    [12, 23, 34, 37], [12, 25, 34, 40], [13, 26, 35, 42], [14, 28, 38, 45], [15, 29, 40, 48],
+    [16, 31, 43, 51], [17, 33, 45, 54], [18, 35, 48, 57], [19, 37, 51, 60], [19, 38, 53, 63],
+    [20, 40, 56, 66], [21, 43, 59, 70], [22, 45, 62, 74], [24, 47, 65, 77], [25, 49, 68, 81],
+];
+
+/// Reads the data out of a sampled symbol: text, version and EC level letter.
+fn read(matrix: &Matrix) -> Result<(String, u8, char), String> {
+    let dim = matrix.dim;
+    let version = ((dim - 17) / 4) as u8;
+    let (ec, pattern) = read_format(matrix).ok_or("unreadable QR format information")?;
+    let qr_version = Version::Normal(version as i16);
+    let width = dim as i16;
+
+    // Data modules in placement order: two-column strips from the right, snaking up and down.
+    let mut bits = Vec::new();
+    let mut right = dim as i64 - 1;
+    while right > 0 {
+        if right == 6 {
+            right -= 1;
+        }
+        let upward = ((dim as i64 - 1 - right) / 2) % 2 == 0;
+        for i in 0..dim {
+            let y = if upward { dim - 1 - i } else { i };
+            for x in [right as usize, right as usize - 1] {
+                let version_block = version >= 7 && ((x < 6 && y + 11 >= dim && y + 8 < dim) || (y < 6 && x + 11 >= dim && x + 8 < dim));
+                if is_functional(qr_version, width, x as i16, y as i16) || version_block {
+                    continue;
+                }
+                bits.push(matrix.dark[y * dim + x] ^ mask(pattern, x, y));
+            }
+        }
+        right -= 2;
+    }
+    let codewords: Vec<u8> = bits.chunks_exact(8).map(|b| b.iter().fold(0, |acc, &bit| acc << 1 | bit as u8)).collect();
+
+    let level = match ec {
+        EcLevel::L => 0,
+        EcLevel::M => 1,
+        EcLevel::Q => 2,
+        EcLevel::H => 3,
+    };
+    let data_len = Bits::new(qr_version).max_len(ec).map_err(|_| "bad QR version")? / 8;
+    let blocks = BLOCKS[version as usize - 1][level] as usize;
+    let ec_len = (codewords.len() - data_len) / blocks;
+    let (short, long) = (data_len / blocks, data_len % blocks);
+    // The last `long` blocks carry one extra data codeword.
+    let sizes: Vec<usize> = (0..blocks).map(|b| short + (b >= blocks - long) as usize).collect();
+    let mut block_data: Vec<Vec<u8>> = sizes.iter().map(|&s| Vec::with_capacity(s + ec_len)).collect();
+    let mut next = codewords.iter();
+    for i in 0..short + 1 {
+        for (b, block) in block_data.iter_mut().enumerate() {
+            if i < sizes[b] {
+                block.push(*next.next().ok_or("QR data is truncated")?);
+            }
+        }
+    }
+    for _ in 0..ec_len {
+        for block in block_data.iter_mut() {
+            block.push(*next.next().ok_or("QR data is truncated")?);
+        }
+    }
+    let mut data = Vec::with_capacity(data_len);
+    for (block, &size) in block_data.iter_mut().zip(&sizes) {
+        correct(block, ec_len)?;
+        data.extend_from_slice(&block[..size]);
+    }
+    let text = parse_segments(&data, version)?;
+    Ok((text, version, ['L', 'M', 'Q', 'H'][level]))
+}
+
+/// GF(256) with the QR polynomial x^8 + x^4 + x^3 + x^2 + 1.
+struct Field {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+impl Field {
+    fn new() -> Field {
+        let mut field = Field { exp: [0; 512], log: [0; 256] };
+        let mut x: u16 = 1;
+        for i in 0..255 {
+            field.exp[i] = x as u8;
+            field.log[x as usize] = i as u8;
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= 0x11d;
+            }
+        }
+        for i in 255..512 {
+            field.exp[i] = field.exp[i - 255];
+        }
+        field
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        if a == 0 {
+            return 0;
+        }
+        self.exp[self.log[a as usize] as usize + 255 - self.log[b as usize] as usize]
+    }
+
+    /// Evaluates a polynomial given lowest degree first.
+    fn eval(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().rev().fold(0, |acc, &c| self.mul(acc, x) ^ c)
+    }
+}
+
+/// Corrects a block (data then EC codewords, highest degree first) in place: syndromes,
+/// Berlekamp-Massey for the error locator, a Chien search for positions, Forney for values.
+fn correct(block: &mut [u8], ec_len: usize) -> Result<(), String> {
+    let gf = Field::new();
+    let n = block.len();
+    let syndromes: Vec<u8> =
+        (0..ec_len).map(|j| block.iter().fold(0, |acc, &c| gf.mul(acc, gf.exp[j]) ^ c)).collect();
+    if syndromes.iter().all(|&s| s == 0) {
+        return Ok(());
+    }
+
+    let (mut lambda, mut prev) = (vec![1u8], vec![1u8]);
+    let (mut len, mut shift, mut prev_d) = (0, 1, 1u8);
+    for i in 0..ec_len {
+        let d = (1..=len).fold(syndromes[i], |acc, k| acc ^ gf.mul(*lambda.get(k).unwrap_or(&0), syndromes[i - k]));
+        if d == 0 {
+            shift += 1;
+            continue;
+        }
+        let scale = gf.div(d, prev_d);
+        let mut next = lambda.clone();
+        next.resize(next.len().max(prev.len() + shift), 0);
+        for (k, &p) in prev.iter().enumerate() {
+            next[k + shift] ^= gf.mul(scale, p);
+        }
+        if 2 * len <= i {
+            len = i + 1 - len;
+            prev = std::mem::replace(&mut lambda, next);
+            prev_d = d;
+            shift = 1;
+        } else {
+            lambda = next;
+            shift += 1;
+        }
+    }
+    if 2 * len > ec_len {
+        return Err("too many errors in QR data".to_string());
+    }
+
+    // omega = S(x) * lambda(x) mod x^ec_len
+    let mut omega = vec![0u8; ec_len];
+    for (i, &s) in syndromes.iter().enumerate() {
+        for (k, &l) in lambda.iter().enumerate() {
+            if i + k < ec_len {
+                omega[i + k] ^= gf.mul(s, l);
+            }
+        }
+    }
+    let derivative: Vec<u8> = lambda.iter().enumerate().skip(1).map(|(k, &l)| if k % 2 == 1 { l } else { 0 }).collect();
+    let mut found = 0;
+    for power in 0..n {
+        // Position `power` counts from the last codeword; its locator is alpha^power.
+        let x_inv = gf.exp[(255 - power % 255) % 255];
+        if gf.eval(&lambda, x_inv) != 0 {
+            continue;
+        }
+        let denom = gf.eval(&derivative, x_inv);
+        if denom == 0 {
+            return Err("too many errors in QR data".to_string());
+        }
+        let value = gf.mul(gf.exp[power % 255], gf.div(gf.eval(&omega, x_inv), denom));
+        block[n - 1 - power] ^= value;
+        found += 1;
+    }
+    if found != len {
+        return Err("too many errors in QR data".to_string());
+    }
+    Ok(())
+}
+
+struct BitStream<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl BitStream<'_> {
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    fn read(&mut self, n: usize) -> Result<u32, String> {
+        if n > self.remaining() {
+            return Err("QR data ends mid-segment".to_string());
+        }
+        let mut value = 0;
+        for _ in 0..n {
+            value = value << 1 | (self.data[self.pos / 8] >> (7 - self.pos % 8)) as u32 & 1;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+}
+
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Concatenates the segments' bytes and reads them as UTF-8, falling back to Latin-1.
+/// Kanji segments are turned back into their Shift JIS bytes.
+fn parse_segments(data: &[u8], version: u8) -> Result<String, String> {
+    let mut bits = BitStream { data, pos: 0 };
+    let mut out = Vec::new();
+    let class = match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        _ => 2,
+    };
+    while bits.remaining() >= 4 {
+        match bits.read(4)? {
+            0 => break,
+            // Numeric: three digits per 10 bits.
+            1 => {
+                let mut count = bits.read([10, 12, 14][class])? as usize;
+                while count > 0 {
+                    let (digits, width) = match count {
+                        1 => (1, 4),
+                        2 => (2, 7),
+                        _ => (3, 10),
+                    };
+                    let value = bits.read(width)?;
+                    out.extend(format!("{:0width$}", value, width = digits).bytes());
+                    count -= digits;
+                }
+            }
+            // Alphanumeric: two characters per 11 bits.
+            2 => {
+                let mut count = bits.read([9, 11, 13][class])? as usize;
+                while count > 0 {
+                    let take = count.min(2);
+                    let value = bits.read(if take == 2 { 11 } else { 6 })? as usize;
+                    let chars = if take == 2 { vec![value / 45, value % 45] } else { vec![value] };
+                    for c in chars {
+                        out.push(*ALPHANUMERIC.get(c).ok_or("invalid alphanumeric QR data")?);
+                    }
+                    count -= take;
+                }
+            }
+            4 => {
+                let count = bits.read([8, 16, 16][class])?;
+                for _ in 0..count {
+                    out.push(bits.read(8)? as u8);
+                }
+            }
+            8 => {
+                let count = bits.read([8, 10, 12][class])?;
+                for _ in 0..count {
+                    let value = bits.read(13)?;
+                    let code = ((value / 0xc0) << 8) | (value % 0xc0);
+                    let code = code + if code < 0x1f00 { 0x8140 } else { 0xc140 };
+                    out.extend_from_slice(&(code as u16).to_be_bytes());
+                }
+            }
+            // ECI designator: one to three bytes; the text is decoded the same way regardless.
+            7 => {
+                let first = bits.read(8)?;
+                if first & 0x80 != 0 {
+                    bits.read(if first & 0x40 == 0 { 8 } else { 16 })?;
+                }
+            }
+            // Structured append header, FNC1 in first position / with application indicator.
+            3 => {
+                bits.read(16)?;
+            }
+            5 => {}
+            9 => {
+                bits.read(8)?;
+            }
+            _ => return Err("unsupported QR segment mode".to_string()),
+        }
+    }
+    Ok(String::from_utf8(out).unwrap_or_else(|e| e.into_bytes().iter().map(|&b| b as char).collect()))
+}
@@ -0,0 +1,225 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use rocket::serde::json::{serde_json, Value};
+use rocket::serde::Serialize;
+
+// Shared response type with content negotiation. Every handler keeps its "native"
+// format (bare text or JSON) for plain curl / browser use, while `?format=` or the
+// Accept header can ask for text, JSON or YAML instead.
+pub struct Reply {
+    payload: Payload,
+    native: Format,
+}
+
+enum Payload {
+    Text(String),
+    Data(Value),
+    Error(ApiError),
+    // Raw bytes (e.g. `?raw=true` decoders) are never re-encoded.
+    Bytes(Vec<u8>),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Format {
+    Text,
+    Json,
+    Yaml,
+}
+
+impl Reply {
+    /// Plain-text endpoint; as JSON/YAML it becomes `{"result": ...}`.
+    pub fn text(s: impl Into<String>) -> Reply {
+        Reply { payload: Payload::Text(s.into()), native: Format::Text }
+    }
+
+    /// JSON endpoint; as text it becomes `key: value` lines.
+    pub fn json(value: impl Serialize) -> Reply {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        Reply { payload: Payload::Data(value), native: Format::Json }
+    }
+
+    /// Raw bytes served as `application/octet-stream` whatever was negotiated.
+    pub fn bytes(bytes: Vec<u8>) -> Reply {
+        Reply { payload: Payload::Bytes(bytes), native: Format::Text }
+    }
+}
+
+// Shared error type. Every failure, from handlers and catchers alike, is served
+// with its HTTP status and the same body shape:
+//   {"error": "input too large (max 100KB)", "code": "input_too_large", "status": 413}
+// `code` is stable and meant for clients to match on; `error` is for humans.
+// Errors are JSON unless text (rendered "Error: msg") or YAML is negotiated.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    status: Status,
+    code: &'static str,
+    message: String,
+    /// Extra context served alongside the standard fields, e.g. the IP an
+    /// `/ip-info` lookup was for.
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl ApiError {
+    pub fn new(status: Status, code: &'static str, msg: impl Into<String>) -> ApiError {
+        ApiError { status, code, message: msg.into(), fields: Vec::new() }
+    }
+
+    /// 400: a required parameter or body is missing or malformed.
+    pub fn bad_request(msg: impl Into<String>) -> ApiError {
+        ApiError::new(Status::BadRequest, "bad_request", msg)
+    }
+
+    /// 413: input over the endpoint's size cap.
+    pub fn too_large(msg: impl Into<String>) -> ApiError {
+        ApiError::new(Status::PayloadTooLarge, "input_too_large", msg)
+    }
+
+    /// 422: well-formed request whose value can't be used (bad hex, out of range...).
+    pub fn invalid(msg: impl Into<String>) -> ApiError {
+        ApiError::new(Status::UnprocessableEntity, "invalid_input", msg)
+    }
+
+    /// 422: an algorithm, case, output or other option we don't support.
+    pub fn unsupported(msg: impl Into<String>) -> ApiError {
+        ApiError::new(Status::UnprocessableEntity, "unsupported_option", msg)
+    }
+
+    /// 503: the endpoint needs server-side configuration that isn't present.
+    pub fn not_configured(msg: impl Into<String>) -> ApiError {
+        ApiError::new(Status::ServiceUnavailable, "not_configured", msg)
+    }
+
+    /// 500: something failed on our side.
+    pub fn internal(msg: impl Into<String>) -> ApiError {
+        ApiError::new(Status::InternalServerError, "internal_error", msg)
+    }
+
+    /// The same error with `prefix: ` in front of the message, e.g. to say which item of a
+    /// batch failed.
+    pub fn prefixed(mut self, prefix: &str) -> ApiError {
+        self.message = format!("{}: {}", prefix, self.message);
+        self
+    }
+
+    /// The same error with an extra `key` in its JSON/YAML body.
+    pub fn with(mut self, key: &'static str, value: impl Into<Value>) -> ApiError {
+        self.fields.push((key, value.into()));
+        self
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    fn to_value(&self) -> Value {
+        let mut value = serde_json::json!({"error": self.message, "code": self.code, "status": self.status.code});
+        for (key, field) in &self.fields {
+            value[*key] = field.clone();
+        }
+        value
+    }
+}
+
+impl From<ApiError> for Reply {
+    fn from(error: ApiError) -> Reply {
+        Reply { payload: Payload::Error(error), native: Format::Json }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        Reply::from(self).respond_to(request)
+    }
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "text" | "txt" | "plain" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    /// `?format=` wins; otherwise the highest-weighted Accept type we can produce.
+    /// `*/*`, a missing header or only unsupported types (e.g. text/html) mean "native".
+    fn negotiate(request: &Request<'_>) -> Option<Format> {
+        if let Some(Ok(name)) = request.query_value::<&str>("format") {
+            if let Some(format) = Format::from_name(name) {
+                return Some(format);
+            }
+        }
+        let accept = request.accept()?;
+        let mut types: Vec<_> = accept.iter().collect();
+        types.sort_by(|a, b| b.weight_or(1.0).total_cmp(&a.weight_or(1.0)));
+        for qt in types {
+            let mt = qt.media_type();
+            match (mt.top().as_str(), mt.sub().as_str()) {
+                ("*", "*") => return None,
+                ("text", "plain") => return Some(Format::Text),
+                ("application", "json") => return Some(Format::Json),
+                ("application", "yaml") | ("application", "x-yaml") | ("text", "yaml") => {
+                    return Some(Format::Yaml)
+                }
+                _ => continue,
+            }
+        }
+        None
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Reply {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let format = Format::negotiate(request).unwrap_or(self.native);
+        let mut status = Status::Ok;
+        let body = match (self.payload, format) {
+            (Payload::Bytes(bytes), _) => return (ContentType::Binary, bytes).respond_to(request),
+            (Payload::Text(s), Format::Text) => (ContentType::Plain, s),
+            (Payload::Text(s), _) => render(serde_json::json!({"result": s}), format),
+            (Payload::Error(e), _) => {
+                status = e.status;
+                match format {
+                    Format::Text => (ContentType::Plain, format!("Error: {}", e.message)),
+                    _ => render(e.to_value(), format),
+                }
+            }
+            (Payload::Data(value), _) => render(value, format),
+        };
+        let mut response = body.respond_to(request)?;
+        response.set_status(status);
+        response.set_header(Header::new("Vary", "Accept"));
+        Ok(response)
+    }
+}
+
+fn render(value: Value, format: Format) -> (ContentType, String) {
+    match format {
+        Format::Text => (ContentType::Plain, to_text(&value)),
+        Format::Json => (ContentType::JSON, value.to_string()),
+        Format::Yaml => (
+            ContentType::new("application", "yaml"),
+            serde_yaml::to_string(&value).unwrap_or_default(),
+        ),
+    }
+}
+
+// Human-oriented rendering of JSON: objects as `key: value` lines, arrays one item
+// per line, nested structures inline as compact JSON.
+fn to_text(value: &Value) -> String {
+    let scalar = |v: &Value| match v {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, scalar(v)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join("\n"),
+        other => scalar(other),
+    }
+}
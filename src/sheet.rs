@@ -0,0 +1,259 @@
+// Label sheets: a page grid of cells, one code per cell with an optional caption
+// underneath, written as a single SVG page or a multi-page PDF. Geometry is worked out
+// once in millimetres as rectangles and text runs, then serialized; the PDF is written
+// by hand (uncompressed, built-in Courier for captions) so no PDF library is needed.
+
+use crate::raster::{xml_escape, Modules};
+
+/// Page width and height in millimetres.
+pub fn page_size(name: &str) -> Option<(f64, f64)> {
+    match name.to_lowercase().as_str() {
+        "a4" => Some((210.0, 297.0)),
+        "letter" => Some((215.9, 279.4)),
+        _ => None,
+    }
+}
+
+pub struct Sheet {
+    pub page: (f64, f64),
+    pub rows: usize,
+    pub cols: usize,
+    /// Page margin on all sides, mm.
+    pub margin: f64,
+    /// Space between labels, mm.
+    pub gap: f64,
+}
+
+impl Sheet {
+    pub fn per_page(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// Width and height of one label, mm.
+    pub fn cell(&self) -> (f64, f64) {
+        let (w, h) = self.page;
+        (
+            (w - 2.0 * self.margin - (self.cols - 1) as f64 * self.gap) / self.cols as f64,
+            (h - 2.0 * self.margin - (self.rows - 1) as f64 * self.gap) / self.rows as f64,
+        )
+    }
+}
+
+/// One label's code, quiet zone included.
+pub struct Label {
+    pub modules: Modules,
+    /// 1D codes stretch their single row of bars into bars; 2D modules stay square.
+    pub linear: bool,
+    pub caption: Option<String>,
+}
+
+struct Rect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+/// A caption line: centre x, baseline y, font size (mm).
+struct Text<'a> {
+    x: f64,
+    y: f64,
+    size: f64,
+    text: &'a str,
+}
+
+/// Monospace advance width per font size, as in Courier.
+const CHAR_WIDTH: f64 = 0.6;
+const MAX_CAPTION_MM: f64 = 4.0;
+
+/// Rectangles and caption for `label` in the cell with its top-left corner at (x, y).
+fn draw<'a>(sheet: &Sheet, label: &'a Label, x: f64, y: f64) -> (Vec<Rect>, Option<Text<'a>>) {
+    let (w, h) = sheet.cell();
+    let pad = 0.06 * w.min(h);
+    let (x, y, w, h) = (x + pad, y + pad, w - 2.0 * pad, h - 2.0 * pad);
+
+    let caption = label.caption.as_deref().filter(|c| !c.is_empty()).map(|text| {
+        let chars = text.chars().count() as f64;
+        let size = (0.14 * h).min(MAX_CAPTION_MM).min(w / (CHAR_WIDTH * chars));
+        Text { x: x + w / 2.0, y: y + h - 0.25 * size, size, text }
+    });
+    let area_h = h - caption.as_ref().map_or(0.0, |t| 1.3 * t.size);
+
+    let modules = &label.modules;
+    let (mw, mh) = (modules.width() as f64, modules.height() as f64);
+    let (module_w, module_h) = if label.linear {
+        (w / mw, area_h.min(0.5 * w))
+    } else {
+        let m = (w / mw).min(area_h / mh);
+        (m, m)
+    };
+    let ox = x + (w - mw * module_w) / 2.0;
+    let oy = y + (area_h - mh * module_h) / 2.0;
+
+    let mut rects = Vec::new();
+    for row in 0..modules.height() {
+        let mut col = 0;
+        while col < modules.width() {
+            if !modules.is_dark(col, row) {
+                col += 1;
+                continue;
+            }
+            let run = (col..modules.width()).take_while(|&i| modules.is_dark(i, row)).count();
+            rects.push(Rect {
+                x: ox + col as f64 * module_w,
+                y: oy + row as f64 * module_h,
+                w: run as f64 * module_w,
+                h: module_h,
+            });
+            col += run;
+        }
+    }
+    (rects, caption)
+}
+
+/// Every label on page `page`, drawn.
+fn page<'a>(sheet: &Sheet, labels: &'a [Label], page: usize) -> Vec<(Vec<Rect>, Option<Text<'a>>)> {
+    let (w, h) = sheet.cell();
+    labels
+        .iter()
+        .skip(page * sheet.per_page())
+        .take(sheet.per_page())
+        .enumerate()
+        .map(|(i, label)| {
+            let (row, col) = (i / sheet.cols, i % sheet.cols);
+            let x = sheet.margin + col as f64 * (w + sheet.gap);
+            let y = sheet.margin + row as f64 * (h + sheet.gap);
+            draw(sheet, label, x, y)
+        })
+        .collect()
+}
+
+pub fn pages(sheet: &Sheet, count: usize) -> usize {
+    count.div_ceil(sheet.per_page())
+}
+
+/// Millimetres with at most three decimals.
+fn mm(v: f64) -> String {
+    let s = format!("{:.3}", v);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// One page of labels; sized in millimetres so it prints at scale.
+pub fn to_svg(sheet: &Sheet, labels: &[Label]) -> String {
+    let (pw, ph) = sheet.page;
+    let mut body = String::new();
+    for (rects, caption) in page(sheet, labels, 0) {
+        for r in rects {
+            body.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                mm(r.x),
+                mm(r.y),
+                mm(r.w),
+                mm(r.h)
+            ));
+        }
+        if let Some(t) = caption {
+            body.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\">{}</text>",
+                mm(t.x),
+                mm(t.y),
+                mm(t.size),
+                xml_escape(t.text)
+            ));
+        }
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}mm\" height=\"{1}mm\" viewBox=\"0 0 {0} {1}\" shape-rendering=\"crispEdges\"><rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/><g fill=\"#000000\">{2}</g></svg>",
+        mm(pw),
+        mm(ph),
+        body
+    )
+}
+
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// All labels, as many pages as they need.
+pub fn to_pdf(sheet: &Sheet, labels: &[Label]) -> Vec<u8> {
+    let (pw, ph) = sheet.page;
+    let count = pages(sheet, labels.len()).max(1);
+    // 1: catalog, 2: page tree, 3: font, then a page and its content stream per page.
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..count).map(|p| format!("{} 0 R", 4 + 2 * p)).collect::<Vec<_>>().join(" "),
+            count
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    for p in 0..count {
+        // Draw in millimetres from the top left, like the SVG.
+        let mut content = format!("{0:.5} 0 0 -{0:.5} 0 {1} cm\n0 g\n", PT_PER_MM, mm(ph * PT_PER_MM)).into_bytes();
+        for (rects, caption) in page(sheet, labels, p) {
+            if !rects.is_empty() {
+                for r in rects {
+                    content.extend_from_slice(format!("{} {} {} {} re\n", mm(r.x), mm(r.y), mm(r.w), mm(r.h)).as_bytes());
+                }
+                content.extend_from_slice(b"f\n");
+            }
+            if let Some(t) = caption {
+                let left = t.x - t.text.chars().count() as f64 * CHAR_WIDTH * t.size / 2.0;
+                // The text matrix flips y back so glyphs stand upright.
+                content.extend_from_slice(
+                    format!("BT /F1 1 Tf {} 0 0 {} {} {} Tm (", mm(t.size), mm(-t.size), mm(left), mm(t.y)).as_bytes(),
+                );
+                content.extend(pdf_string(t.text));
+                content.extend_from_slice(b") Tj ET\n");
+            }
+        }
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                mm(pw * PT_PER_MM),
+                mm(ph * PT_PER_MM),
+                5 + 2 * p
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+    );
+    pdf
+}
+
+/// A PDF literal string body in WinAnsi (Latin-1 for our purposes); other characters
+/// become '?'.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let b = match c as u32 {
+            0x20..=0x7e | 0xa0..=0xff => c as u32 as u8,
+            _ => b'?',
+        };
+        if matches!(b, b'\\' | b'(' | b')') {
+            out.push(b'\\');
+        }
+        out.push(b);
+    }
+    out
+}
@@ -25,7 +25,12 @@ pub struct EchoResponse {
     pub method: String,
     pub query: HashMap<String, String>,
     pub headers: Map<String, String>,
+    pub content_type: Option<String>,
+    // The Content-Length header for GET, HEAD and OPTIONS; the bytes read otherwise.
+    pub content_length: Option<usize>,
     pub body: String,
+    // "utf8", or "base64" when the body isn't valid UTF-8
+    pub body_encoding: String,
 }
 
 #[derive(Serialize)]